@@ -0,0 +1,386 @@
+//! Repeated timing of each day's generator and parts, along with the number
+//! of allocations they make, in a report that can be saved as JSON and
+//! compared against a later run.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicU64, Ordering},
+    time::Instant,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::solution::{self, RunError};
+
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static ALLOCATED_BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// The system allocator, counting every allocation made through it. A
+/// binary has to install it with `#[global_allocator]`, otherwise every
+/// allocation count in a report is zero.
+pub struct CountingAlloc;
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size() as u64, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(layout.size() as u64, Ordering::Relaxed);
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        ALLOCATED_BYTES.fetch_add(new_size as u64, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+fn allocation_counters() -> (u64, u64) {
+    (
+        ALLOCATIONS.load(Ordering::Relaxed),
+        ALLOCATED_BYTES.load(Ordering::Relaxed),
+    )
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stage {
+    Parse,
+    Part1,
+    Part2,
+}
+
+impl fmt::Display for Stage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Stage::Parse => write!(f, "parse"),
+            Stage::Part1 => write!(f, "part 1"),
+            Stage::Part2 => write!(f, "part 2"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stats {
+    pub min: u64,
+    pub median: u64,
+    pub max: u64,
+}
+
+impl Stats {
+    /// Panics if there are no samples.
+    pub fn from_samples(mut samples: Vec<u64>) -> Self {
+        samples.sort_unstable();
+
+        Self {
+            min: samples[0],
+            median: samples[samples.len() / 2],
+            max: samples[samples.len() - 1],
+        }
+    }
+}
+
+/// Timings of one stage of one day over every run. Allocation counts are
+/// the median per run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Entry {
+    pub day: u8,
+    pub stage: Stage,
+    pub nanos: Stats,
+    pub allocations: u64,
+    pub allocated_bytes: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct BenchReport {
+    pub runs: u32,
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Debug)]
+pub enum ReportError {
+    Io(PathBuf, io::Error),
+    Json(PathBuf, serde_json::Error),
+}
+
+impl fmt::Display for ReportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportError::Io(path, e) => write!(f, "cannot read {}: {e}", path.display()),
+            ReportError::Json(path, e) => write!(f, "invalid report {}: {e}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for ReportError {}
+
+struct Sample {
+    nanos: u64,
+    allocations: u64,
+    allocated_bytes: u64,
+}
+
+fn measure<T>(f: impl FnOnce() -> T) -> (T, Sample) {
+    let (allocations, allocated_bytes) = allocation_counters();
+    let start = Instant::now();
+    let result = f();
+    let nanos = start.elapsed().as_nanos() as u64;
+    let (allocations_after, allocated_bytes_after) = allocation_counters();
+
+    let sample = Sample {
+        nanos,
+        allocations: allocations_after - allocations,
+        allocated_bytes: allocated_bytes_after - allocated_bytes,
+    };
+
+    (result, sample)
+}
+
+fn entry(day: u8, stage: Stage, samples: Vec<Sample>) -> Entry {
+    let median = |samples: Vec<u64>| Stats::from_samples(samples).median;
+
+    Entry {
+        day,
+        stage,
+        nanos: Stats::from_samples(samples.iter().map(|s| s.nanos).collect()),
+        allocations: median(samples.iter().map(|s| s.allocations).collect()),
+        allocated_bytes: median(samples.iter().map(|s| s.allocated_bytes).collect()),
+    }
+}
+
+/// Runs the generator and both parts of `day` on `input`, `runs` times
+/// each. Returns no entries if there is no solver for `day`.
+pub fn bench_day(day: u8, input: &str, runs: u32) -> Result<Vec<Entry>, RunError> {
+    let runs = runs.max(1);
+    let solvers = solution::solvers()
+        .iter()
+        .filter(|s| s.day == day)
+        .collect::<Vec<_>>();
+
+    let Some(first) = solvers.first() else {
+        return Ok(Vec::new());
+    };
+
+    let mut samples = Vec::new();
+    let mut parsed = None;
+    for _ in 0..runs {
+        let (result, sample) = measure(|| first.parse(input));
+        samples.push(sample);
+        parsed = Some(result?);
+    }
+
+    let parsed = parsed.unwrap();
+    let mut entries = vec![entry(day, Stage::Parse, samples)];
+
+    for solver in solvers {
+        let mut samples = Vec::new();
+        for _ in 0..runs {
+            let (result, sample) = measure(|| solver.solve(&parsed));
+            samples.push(sample);
+            result?;
+        }
+        let stage = if solver.part == 1 {
+            Stage::Part1
+        } else {
+            Stage::Part2
+        };
+
+        entries.push(entry(day, stage, samples));
+    }
+
+    Ok(entries)
+}
+
+impl BenchReport {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ReportError> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| ReportError::Io(path.to_path_buf(), e))?;
+
+        serde_json::from_str(&text).map_err(|e| ReportError::Json(path.to_path_buf(), e))
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+
+    /// Every median time or allocation count in `self` that grew by more
+    /// than `threshold` (0.1 for 10%) over the same day and stage in
+    /// `baseline`. Entries missing from either report are skipped.
+    pub fn regressions(&self, baseline: &BenchReport, threshold: f64) -> Vec<Regression> {
+        let mut regressions = Vec::new();
+
+        for entry in &self.entries {
+            let Some(before) = baseline
+                .entries
+                .iter()
+                .find(|e| e.day == entry.day && e.stage == entry.stage)
+            else {
+                continue;
+            };
+
+            let metrics = [
+                (Metric::Time, before.nanos.median, entry.nanos.median),
+                (Metric::Allocations, before.allocations, entry.allocations),
+            ];
+
+            for (metric, before, after) in metrics {
+                if after as f64 > before as f64 * (1.0 + threshold) {
+                    regressions.push(Regression {
+                        day: entry.day,
+                        stage: entry.stage,
+                        metric,
+                        before,
+                        after,
+                    });
+                }
+            }
+        }
+
+        regressions
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Metric {
+    /// Median wall time, in nanoseconds.
+    Time,
+    /// Median allocations per run.
+    Allocations,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Regression {
+    pub day: u8,
+    pub stage: Stage,
+    pub metric: Metric,
+    pub before: u64,
+    pub after: u64,
+}
+
+impl fmt::Display for Regression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let what = match self.metric {
+            Metric::Time => "median time (ns)",
+            Metric::Allocations => "allocations",
+        };
+        let change = if self.before == 0 {
+            "new".to_string()
+        } else {
+            format!(
+                "{:+.1}%",
+                (self.after as f64 / self.before as f64 - 1.0) * 100.0
+            )
+        };
+
+        write!(
+            f,
+            "day {:>2}, {}: {what} {} -> {} ({change})",
+            self.day, self.stage, self.before, self.after
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(entries: &[(u8, Stage, u64, u64)]) -> BenchReport {
+        BenchReport {
+            runs: 1,
+            entries: entries
+                .iter()
+                .map(|&(day, stage, nanos, allocations)| Entry {
+                    day,
+                    stage,
+                    nanos: Stats {
+                        min: nanos,
+                        median: nanos,
+                        max: nanos,
+                    },
+                    allocations,
+                    allocated_bytes: 0,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn stats() {
+        assert_eq!(
+            Stats::from_samples(vec![5, 1, 9, 3, 7]),
+            Stats {
+                min: 1,
+                median: 5,
+                max: 9
+            }
+        );
+    }
+
+    #[test]
+    fn bench_example() {
+        let entries = bench_day(1, "3   4\n4   3\n", 3).unwrap();
+
+        let stages = entries.iter().map(|e| e.stage).collect::<Vec<_>>();
+        assert_eq!(stages, [Stage::Parse, Stage::Part1, Stage::Part2]);
+        assert!(entries.iter().all(|e| e.nanos.min <= e.nanos.max));
+
+        assert!(bench_day(1, "3 x\n", 3).is_err());
+        assert_eq!(bench_day(25, "", 3).unwrap(), []);
+    }
+
+    #[test]
+    fn report_round_trips() {
+        let report = report(&[(1, Stage::Parse, 100, 2), (1, Stage::Part2, 50, 0)]);
+
+        assert_eq!(
+            serde_json::from_str::<BenchReport>(&report.to_json()).unwrap(),
+            report
+        );
+    }
+
+    #[test]
+    fn regressions_above_threshold() {
+        let baseline = report(&[
+            (1, Stage::Parse, 1000, 10),
+            (1, Stage::Part1, 1000, 0),
+            (2, Stage::Parse, 1000, 10),
+        ]);
+        let current = report(&[
+            (1, Stage::Parse, 1050, 10),
+            (1, Stage::Part1, 1200, 1),
+            (3, Stage::Parse, 9000, 90),
+        ]);
+
+        assert_eq!(
+            current.regressions(&baseline, 0.1),
+            [
+                Regression {
+                    day: 1,
+                    stage: Stage::Part1,
+                    metric: Metric::Time,
+                    before: 1000,
+                    after: 1200,
+                },
+                Regression {
+                    day: 1,
+                    stage: Stage::Part1,
+                    metric: Metric::Allocations,
+                    before: 0,
+                    after: 1,
+                },
+            ]
+        );
+        assert_eq!(current.regressions(&baseline, 0.5).len(), 1);
+    }
+}
@@ -0,0 +1,395 @@
+use std::fmt::Debug;
+
+use crate::{day1, day11, day2, day4, day9, grid::Grid, rng::Rng};
+
+/// An input on which the reference and optimized implementations disagree.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// The seed that regenerates this input on its own.
+    pub seed: u64,
+    pub input: String,
+    pub reference: String,
+    pub optimized: String,
+}
+
+/// Runs `cases` generated inputs through both implementations and returns
+/// the first one where they disagree. Case `n` is generated from `seed + n`
+/// with a size growing from 1 up to `max_size`, so earlier (and smaller)
+/// inputs are tried first.
+pub fn find_divergence<I, O>(
+    cases: u64,
+    seed: u64,
+    max_size: usize,
+    generate: impl Fn(&mut Rng, usize) -> I,
+    reference: impl Fn(&I) -> O,
+    optimized: impl Fn(&I) -> O,
+) -> Option<Divergence>
+where
+    I: Debug,
+    O: PartialEq + Debug,
+{
+    (0..cases).find_map(|case| {
+        let seed = seed.wrapping_add(case);
+        let size = 1 + (case as usize * max_size.saturating_sub(1)) / cases.max(1) as usize;
+        let input = generate(&mut Rng::new(seed), size);
+
+        let expected = reference(&input);
+        let actual = optimized(&input);
+
+        (expected != actual).then(|| Divergence {
+            seed,
+            input: format!("{input:?}"),
+            reference: format!("{expected:?}"),
+            optimized: format!("{actual:?}"),
+        })
+    })
+}
+
+/// A reference/optimized pair for one day.
+#[derive(Clone, Copy)]
+pub struct Check {
+    pub day: u8,
+    pub name: &'static str,
+    run: fn(u64, u64) -> Option<Divergence>,
+}
+
+impl Check {
+    /// Runs `cases` generated inputs starting from `seed`.
+    pub fn run(&self, cases: u64, seed: u64) -> Option<Divergence> {
+        (self.run)(cases, seed)
+    }
+}
+
+/// Two lists drawn from a small range, so that IDs repeat.
+fn location_lists(rng: &mut Rng, size: usize) -> (Vec<i64>, Vec<i64>) {
+    let mut list = || (0..size).map(|_| rng.range(-20..=20)).collect();
+
+    (list(), list())
+}
+
+#[derive(Debug)]
+enum ListUpdate {
+    Insert(day1::Side, i64),
+    Remove(day1::Side, i64),
+}
+
+/// Insertions and removals of IDs from a small range, mostly insertions.
+fn list_updates(rng: &mut Rng, size: usize) -> Vec<ListUpdate> {
+    (0..size)
+        .map(|_| {
+            let side = *rng.choose(&[day1::Side::Left, day1::Side::Right]);
+            let id = rng.range(-10..=10);
+
+            if rng.chance(0.7) {
+                ListUpdate::Insert(side, id)
+            } else {
+                ListUpdate::Remove(side, id)
+            }
+        })
+        .collect()
+}
+
+/// Replays `updates` on plain lists, re-solving both parts after each one.
+fn replay_lists(updates: &[ListUpdate]) -> Vec<(i64, i128)> {
+    let mut lists = vec![Vec::new(), Vec::new()];
+
+    updates
+        .iter()
+        .map(|update| {
+            let (side, id) = match update {
+                ListUpdate::Insert(side, id) | ListUpdate::Remove(side, id) => (side, id),
+            };
+            let list = match side {
+                day1::Side::Left => &mut lists[0],
+                day1::Side::Right => &mut lists[1],
+            };
+
+            match update {
+                ListUpdate::Insert(..) => list.push(*id),
+                ListUpdate::Remove(..) => {
+                    if let Some(idx) = list.iter().position(|x| x == id) {
+                        list.remove(idx);
+                    }
+                }
+            }
+
+            (day1::solve_part1(&lists), day1::solve_part2(&lists))
+        })
+        .collect()
+}
+
+fn track_lists(updates: &[ListUpdate]) -> Vec<(i64, i128)> {
+    let mut lists = day1::IncrementalLists::new();
+
+    updates
+        .iter()
+        .map(|update| {
+            match *update {
+                ListUpdate::Insert(side, id) => lists.insert(side, id),
+                ListUpdate::Remove(side, id) => {
+                    lists.remove(side, id);
+                }
+            }
+
+            (lists.distance(), lists.similarity())
+        })
+        .collect()
+}
+
+/// A short report with small steps, random safety rules and a tolerance
+/// of 0 to 3 removals.
+fn dampened_report(rng: &mut Rng, size: usize) -> (Vec<i64>, day2::SafetyRules, usize) {
+    let mut level = rng.range(1..=20);
+    let report = (0..size.min(9))
+        .map(|_| {
+            level += rng.range(-4..=4);
+            level
+        })
+        .collect();
+
+    let min_step = rng.range(1..=2);
+    let rules = if rng.chance(0.5) {
+        day2::SafetyRules::default()
+    } else {
+        day2::SafetyRules {
+            min_step,
+            max_step: min_step + rng.range(0..=3),
+            allow_plateaus: rng.chance(0.5),
+            direction: *rng.choose(&[
+                day2::Direction::Increasing,
+                day2::Direction::Decreasing,
+                day2::Direction::Either,
+            ]),
+        }
+    };
+
+    (report, rules, rng.below(4) as usize)
+}
+
+/// A grid of few letters, so that words overlap, and a handful of words
+/// that include palindromes and prefixes of each other.
+fn word_search(rng: &mut Rng, size: usize) -> (Grid<char>, Vec<String>) {
+    const LETTERS: &[char] = &['A', 'B', 'C'];
+
+    let width = 1 + rng.below(size.min(12) as u64) as usize;
+    let height = 1 + rng.below(size.min(12) as u64) as usize;
+    let cells = (0..width * height).map(|_| *rng.choose(LETTERS)).collect();
+
+    let words = (0..1 + rng.below(5))
+        .map(|_| {
+            let half = (0..rng.below(3))
+                .map(|_| *rng.choose(LETTERS))
+                .collect::<String>();
+
+            match rng.below(3) {
+                0 => half.chars().chain(half.chars().rev()).collect(),
+                1 => format!(
+                    "{half}{}{}",
+                    rng.choose(LETTERS),
+                    half.chars().rev().collect::<String>()
+                ),
+                _ => format!("{half}{}", rng.choose(LETTERS)),
+            }
+        })
+        .collect();
+
+    (Grid::new(width, height, cells), words)
+}
+
+/// Files and free spans 0 to 9 blocks long.
+fn disk_map(rng: &mut Rng, size: usize) -> Vec<u32> {
+    (0..size).map(|_| rng.below(10) as u32).collect()
+}
+
+fn stones(rng: &mut Rng, size: usize) -> (Vec<usize>, usize) {
+    let stones = (0..size.min(8))
+        .map(|_| match rng.below(3) {
+            0 => rng.below(10),
+            1 => rng.below(10_000),
+            _ => rng.below(1 << 40),
+        } as usize)
+        .collect();
+
+    (stones, rng.below(size.min(20) as u64 + 1) as usize)
+}
+
+static CHECKS: &[Check] = &[
+    Check {
+        day: 1,
+        name: "similarity score",
+        run: |cases, seed| {
+            find_divergence(
+                cases,
+                seed,
+                60,
+                location_lists,
+                |(left, right)| i128::from(day1::similarity_score_naive(left, right)),
+                |(left, right)| day1::similarity_score(left.iter().copied(), right.iter().copied()),
+            )
+        },
+    },
+    Check {
+        day: 1,
+        name: "incremental lists",
+        run: |cases, seed| {
+            find_divergence(
+                cases,
+                seed,
+                60,
+                list_updates,
+                |updates| replay_lists(updates),
+                |updates| track_lists(updates),
+            )
+        },
+    },
+    Check {
+        day: 2,
+        name: "problem dampener",
+        run: |cases, seed| {
+            find_divergence(
+                cases,
+                seed,
+                9,
+                dampened_report,
+                |(report, rules, k)| day2::is_dampened_safe_naive(report, rules, *k),
+                |(report, rules, k)| day2::is_dampened_safe(report, rules, *k),
+            )
+        },
+    },
+    Check {
+        day: 2,
+        name: "dampened level",
+        run: |cases, seed| {
+            find_divergence(
+                cases,
+                seed,
+                9,
+                dampened_report,
+                |(report, rules, _)| {
+                    if day2::is_report_valid(report, rules) {
+                        return None;
+                    }
+
+                    (0..report.len()).find(|skip| {
+                        let mut report = report.clone();
+                        report.remove(*skip);
+
+                        day2::is_report_valid(&report, rules)
+                    })
+                },
+                |(report, rules, _)| match day2::report_verdict(report, rules) {
+                    day2::ReportVerdict::Safe => None,
+                    day2::ReportVerdict::Unsafe { dampened, .. } => dampened,
+                },
+            )
+        },
+    },
+    Check {
+        day: 4,
+        name: "word search",
+        run: |cases, seed| {
+            find_divergence(
+                cases,
+                seed,
+                12,
+                word_search,
+                |(grid, words)| {
+                    words
+                        .iter()
+                        .map(|word| {
+                            let mut occurrences = day4::find_word_naive(grid, word);
+                            occurrences.sort();
+                            occurrences
+                        })
+                        .collect::<Vec<_>>()
+                },
+                |(grid, words)| {
+                    day4::WordSearch::new(words)
+                        .find(grid)
+                        .into_iter()
+                        .map(|found| {
+                            let mut occurrences = found.occurrences;
+                            occurrences.sort();
+                            occurrences
+                        })
+                        .collect::<Vec<_>>()
+                },
+            )
+        },
+    },
+    Check {
+        day: 9,
+        name: "block compaction",
+        run: |cases, seed| {
+            find_divergence(
+                cases,
+                seed,
+                40,
+                disk_map,
+                |input| day9::solve_part1_naive(input),
+                |input| day9::solve_part1(input),
+            )
+        },
+    },
+    Check {
+        day: 9,
+        name: "file compaction",
+        run: |cases, seed| {
+            find_divergence(
+                cases,
+                seed,
+                40,
+                disk_map,
+                |input| day9::solve_part2_naive(input),
+                |input| day9::solve_part2(input),
+            )
+        },
+    },
+    Check {
+        day: 11,
+        name: "stone cycler",
+        run: |cases, seed| {
+            find_divergence(
+                cases,
+                seed,
+                20,
+                stones,
+                |(stones, blinks)| day11::count_stones_naive(stones, *blinks),
+                |(stones, blinks)| day11::count_stones(stones, *blinks),
+            )
+        },
+    },
+];
+
+pub fn checks() -> &'static [Check] {
+    CHECKS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_first_divergence() {
+        let divergence = find_divergence(
+            100,
+            7,
+            100,
+            |_, size| size,
+            |size| *size,
+            |size| (*size).min(50),
+        )
+        .unwrap();
+
+        assert_eq!(divergence.input, "51");
+        assert_eq!(divergence.reference, "51");
+        assert_eq!(divergence.optimized, "50");
+    }
+
+    #[test]
+    fn optimized_solvers_match_references() {
+        for check in checks() {
+            assert_eq!(check.run(100, 0), None, "{}", check.name);
+        }
+    }
+}
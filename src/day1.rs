@@ -1,33 +1,623 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    hash::Hash,
+};
+
 use aoc_runner_derive::{aoc, aoc_generator};
 
+use crate::{
+    parse::{lines, ParseError},
+    solution::{Answer, Solution, SolveError},
+};
+
+/// One list per column. The first line sets the number of columns, at
+/// least two, and every other line must have as many location IDs.
 #[aoc_generator(day1)]
-pub fn input_gen(input: &str) -> (Vec<i64>, Vec<i64>) {
-    input
-        .lines()
-        .map(|l| {
-            let mut parts = l.split_whitespace().map(|d| d.parse::<i64>().unwrap());
-            (parts.next().unwrap(), parts.next().unwrap())
-        })
-        .unzip()
+pub fn input_gen(input: &str) -> Result<Vec<Vec<i64>>, ParseError> {
+    let mut columns: Vec<Vec<i64>> = Vec::new();
+
+    for l in lines(1, input) {
+        let fields = l.text().split_whitespace().collect::<Vec<_>>();
+
+        if columns.is_empty() {
+            if fields.len() < 2 {
+                return Err(l.error_at_end("expected at least two location IDs"));
+            }
+
+            columns.resize(fields.len(), Vec::new());
+        }
+
+        let n = columns.len();
+        if let Some(extra) = fields.get(n) {
+            return Err(l.error_at(extra, format!("expected only {n} location IDs")));
+        }
+        if fields.len() < n {
+            return Err(l.error_at_end(format!("expected {n} location IDs")));
+        }
+
+        for (column, field) in columns.iter_mut().zip(fields) {
+            column.push(l.parse(field)?);
+        }
+    }
+
+    Ok(columns)
 }
 
+/// The distance between the first two lists.
 #[aoc(day1, part1)]
-pub fn solve_part1(input: &(Vec<i64>, Vec<i64>)) -> i64 {
-    let mut a = input.0.clone();
-    let mut b = input.1.clone();
+pub fn solve_part1(input: &[Vec<i64>]) -> i64 {
+    match input {
+        [left, right, ..] => distance(left, right),
+        _ => 0,
+    }
+}
 
-    a.sort();
-    b.sort();
+/// The similarity score of the first list against the second.
+#[aoc(day1, part2)]
+pub fn solve_part2(input: &[Vec<i64>]) -> i128 {
+    match input {
+        [left, right, ..] => similarity_score(left.iter().copied(), right.iter().copied()),
+        _ => 0,
+    }
+}
 
-    a.into_iter().zip(b).map(|(a, b)| (a - b).abs()).sum()
+/// Sum of the differences between the smallest IDs of both lists, the
+/// second smallest, and so on. If one list is longer, its largest IDs are
+/// left unpaired.
+pub fn distance(left: &[i64], right: &[i64]) -> i64 {
+    let mut left = left.to_vec();
+    let mut right = right.to_vec();
+
+    left.sort();
+    right.sort();
+
+    sorted_distance(&left, &right)
 }
 
-#[aoc(day1, part2)]
-pub fn solve_part2(input: &(Vec<i64>, Vec<i64>)) -> i64 {
-    let a = &input.0;
-    let b = &input.1;
+fn sorted_distance(left: &[i64], right: &[i64]) -> i64 {
+    left.iter().zip(right).map(|(a, b)| (a - b).abs()).sum()
+}
+
+/// An integer type of at most 64 bits. Any such ID times any number of
+/// occurrences fits in an `i128`.
+pub trait LocationId: Copy + Eq + Hash {
+    fn widen(self) -> i128;
+}
+
+macro_rules! location_id {
+    ($($t:ty),*) => {
+        $(
+            impl LocationId for $t {
+                fn widen(self) -> i128 {
+                    self as i128
+                }
+            }
+        )*
+    };
+}
+
+location_id!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+/// Sum of every ID in `left` times the number of times it appears in
+/// `right`, in linear time. The sum only overflows an `i128` if 2^63 or
+/// more pairs of IDs are equal.
+pub fn similarity_score<T: LocationId>(
+    left: impl IntoIterator<Item = T>,
+    right: impl IntoIterator<Item = T>,
+) -> i128 {
+    score_against(left, &count_ids(right))
+}
+
+fn count_ids<T: LocationId>(list: impl IntoIterator<Item = T>) -> HashMap<T, i128> {
+    let mut counts = HashMap::new();
+    for id in list {
+        *counts.entry(id).or_default() += 1;
+    }
+
+    counts
+}
+
+/// [`similarity_score`] against a list already counted by [`count_ids`].
+fn score_against<T: LocationId>(
+    left: impl IntoIterator<Item = T>,
+    counts: &HashMap<T, i128>,
+) -> i128 {
+    left.into_iter()
+        .filter_map(|id| Some(id.widen() * counts.get(&id)?))
+        .sum()
+}
+
+/// One pair of the sorted pairing behind [`distance`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pair {
+    pub left: i64,
+    pub right: i64,
+    pub distance: i64,
+}
+
+/// The pairs that add up to a [`distance`], smallest IDs first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairingExplanation {
+    pub pairs: Vec<Pair>,
+    pub total: i64,
+    /// The largest distance of any pair, `None` if there are no pairs.
+    pub max_gap: Option<i64>,
+    /// The middle distance once sorted, the upper one for an even number
+    /// of pairs. `None` if there are no pairs.
+    pub median_gap: Option<i64>,
+    /// The number of pairs with a distance of zero.
+    pub exact_matches: usize,
+}
+
+/// The sorted pairing of `left` and `right` along with a few statistics.
+pub fn explain_distance(left: &[i64], right: &[i64]) -> PairingExplanation {
+    let mut left = left.to_vec();
+    let mut right = right.to_vec();
+
+    left.sort();
+    right.sort();
+
+    let pairs = left
+        .into_iter()
+        .zip(right)
+        .map(|(left, right)| Pair {
+            left,
+            right,
+            distance: (left - right).abs(),
+        })
+        .collect::<Vec<_>>();
+
+    let mut gaps = pairs.iter().map(|p| p.distance).collect::<Vec<_>>();
+    gaps.sort();
+
+    PairingExplanation {
+        total: gaps.iter().sum(),
+        max_gap: gaps.last().copied(),
+        median_gap: gaps.get(gaps.len() / 2).copied(),
+        exact_matches: gaps.iter().take_while(|gap| **gap == 0).count(),
+        pairs,
+    }
+}
+
+impl fmt::Display for PairingExplanation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let gap = |gap: Option<i64>| gap.map(|gap| gap.to_string()).unwrap_or("-".into());
+
+        writeln!(
+            f,
+            "{:>6} {:>12} {:>12} {:>12}",
+            "pair", "left", "right", "distance"
+        )?;
+        for (idx, pair) in self.pairs.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>6} {:>12} {:>12} {:>12}",
+                idx + 1,
+                pair.left,
+                pair.right,
+                pair.distance
+            )?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "total distance: {}", self.total)?;
+        writeln!(f, "max gap:        {}", gap(self.max_gap))?;
+        writeln!(f, "median gap:     {}", gap(self.median_gap))?;
+        write!(f, "exact matches:  {}", self.exact_matches)
+    }
+}
+
+/// [`distance`] and [`similarity_score`] between every pair of lists.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PairMatrices {
+    /// `distance[i][j]` is the distance between lists `i` and `j`. It is
+    /// symmetric, with zeros on the diagonal.
+    pub distance: Vec<Vec<i64>>,
+    /// `similarity[i][j]` is the similarity score of list `i` against list
+    /// `j`. It is not symmetric.
+    pub similarity: Vec<Vec<i128>>,
+}
+
+/// `f(i, j)` for every `i` and `j` below `n`.
+fn matrix<T>(n: usize, f: impl Fn(usize, usize) -> T) -> Vec<Vec<T>> {
+    (0..n).map(|i| (0..n).map(|j| f(i, j)).collect()).collect()
+}
+
+/// Sorts and counts every list once, then compares each pair.
+pub fn pair_matrices(lists: &[Vec<i64>]) -> PairMatrices {
+    let sorted = lists
+        .iter()
+        .map(|list| {
+            let mut list = list.clone();
+            list.sort();
+            list
+        })
+        .collect::<Vec<_>>();
+
+    let counts = lists
+        .iter()
+        .map(|list| count_ids(list.iter().copied()))
+        .collect::<Vec<_>>();
+
+    PairMatrices {
+        distance: matrix(lists.len(), |i, j| sorted_distance(&sorted[i], &sorted[j])),
+        similarity: matrix(lists.len(), |i, j| {
+            score_against(lists[i].iter().copied(), &counts[j])
+        }),
+    }
+}
 
-    a.iter()
-        .map(|a| a * b.iter().filter(|b| a == *b).count() as i64)
+/// [`similarity_score`] by scanning all of `right` for every left ID.
+pub(crate) fn similarity_score_naive(left: &[i64], right: &[i64]) -> i64 {
+    left.iter()
+        .map(|a| a * right.iter().filter(|b| a == *b).count() as i64)
         .sum()
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// Both location lists as ordered multisets, updated one ID at a time.
+///
+/// Inserting or removing an ID takes logarithmic time, and keeps the
+/// similarity score ([`solve_part2`]) up to date. The total distance
+/// ([`solve_part1`]) is not kept up to date, see
+/// [`distance`](Self::distance).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IncrementalLists {
+    left: BTreeMap<i64, usize>,
+    right: BTreeMap<i64, usize>,
+    left_len: usize,
+    right_len: usize,
+    similarity: i128,
+}
+
+impl IncrementalLists {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self, side: Side) -> usize {
+        match side {
+            Side::Left => self.left_len,
+            Side::Right => self.right_len,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.left_len == 0 && self.right_len == 0
+    }
+
+    /// How many times `id` appears in the list on `side`.
+    pub fn count(&self, side: Side, id: i64) -> usize {
+        let list = match side {
+            Side::Left => &self.left,
+            Side::Right => &self.right,
+        };
+
+        list.get(&id).copied().unwrap_or_default()
+    }
+
+    pub fn insert(&mut self, side: Side, id: i64) {
+        let (list, len, other) = match side {
+            Side::Left => (&mut self.left, &mut self.left_len, &self.right),
+            Side::Right => (&mut self.right, &mut self.right_len, &self.left),
+        };
+
+        *list.entry(id).or_default() += 1;
+        *len += 1;
+        self.add_similarity(id, other.get(&id).copied().unwrap_or_default() as i128);
+    }
+
+    /// Adds `count` pairs of `id` to the similarity score. An `i64` times a
+    /// `usize` always fits in an `i128`, and the sum only overflows with
+    /// 2^64 or more pairs of equal IDs.
+    fn add_similarity(&mut self, id: i64, count: i128) {
+        self.similarity = i128::from(id)
+            .checked_mul(count)
+            .and_then(|delta| self.similarity.checked_add(delta))
+            .expect("similarity score overflows an i128");
+    }
+
+    /// Removes one occurrence of `id`, returning whether there was one.
+    pub fn remove(&mut self, side: Side, id: i64) -> bool {
+        let (list, len, other) = match side {
+            Side::Left => (&mut self.left, &mut self.left_len, &self.right),
+            Side::Right => (&mut self.right, &mut self.right_len, &self.left),
+        };
+
+        let Some(count) = list.get_mut(&id) else {
+            return false;
+        };
+
+        *count -= 1;
+        if *count == 0 {
+            list.remove(&id);
+        }
+        *len -= 1;
+        self.add_similarity(id, -(other.get(&id).copied().unwrap_or_default() as i128));
+
+        true
+    }
+
+    /// The [`distance`] between the current lists.
+    ///
+    /// Unlike [`similarity`](Self::similarity), this is computed on every
+    /// call by walking both multisets once. That takes time linear in the
+    /// number of distinct IDs, with no sorting. The lists are paired in
+    /// sorted order, so inserting one ID shifts the pairing of every larger
+    /// ID. The resulting change in distance is a sum over all of them, and
+    /// no ordered index over the IDs gives it in logarithmic time.
+    pub fn distance(&self) -> i64 {
+        let mut left = self.left.iter().map(|(id, n)| (*id, *n));
+        let mut right = self.right.iter().map(|(id, n)| (*id, *n));
+        let (mut a, mut b) = (left.next(), right.next());
+        let mut distance = 0;
+
+        while let (Some((x, n)), Some((y, m))) = (a, b) {
+            let paired = n.min(m);
+            distance += (x - y).abs() * paired as i64;
+
+            a = if n == paired {
+                left.next()
+            } else {
+                Some((x, n - paired))
+            };
+            b = if m == paired {
+                right.next()
+            } else {
+                Some((y, m - paired))
+            };
+        }
+
+        distance
+    }
+
+    /// The [`similarity_score`] of the current lists.
+    pub fn similarity(&self) -> i128 {
+        self.similarity
+    }
+
+    pub fn from_lists(left: &[i64], right: &[i64]) -> Self {
+        let mut lists = Self::new();
+        for id in left {
+            lists.insert(Side::Left, *id);
+        }
+        for id in right {
+            lists.insert(Side::Right, *id);
+        }
+
+        lists
+    }
+}
+
+pub struct Day1;
+
+impl Solution for Day1 {
+    const DAY: u8 = 1;
+
+    type Input = Vec<Vec<i64>>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_gen(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(solve_part1(input).into())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(solve_part2(input).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+3   4
+4   3
+2   5
+1   3
+3   9
+3   3
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(solve_part1(&input_gen(EXAMPLE).unwrap()), 11);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(solve_part2(&input_gen(EXAMPLE).unwrap()), 31);
+    }
+
+    #[test]
+    fn similarity_score_any_integers() {
+        assert_eq!(similarity_score([3_u8, 4, 2], [3, 3, 4]), 10);
+        assert_eq!(similarity_score([-1_i32, 5], [-1, -1]), -2);
+        assert_eq!(similarity_score(Vec::<i64>::new(), [1, 2]), 0);
+        assert_eq!(
+            similarity_score([u64::MAX, 1], [u64::MAX, u64::MAX, 1]),
+            2 * u64::MAX as i128 + 1
+        );
+        assert_eq!(
+            similarity_score([usize::MAX], [usize::MAX]),
+            usize::MAX as i128
+        );
+        assert_eq!(
+            similarity_score([i64::MIN, i64::MIN, 0], [i64::MIN; 3]),
+            6 * i64::MIN as i128
+        );
+    }
+
+    #[test]
+    fn incremental_lists_example() {
+        let input = input_gen(EXAMPLE).unwrap();
+        let mut lists = IncrementalLists::new();
+
+        for (a, b) in input[0].iter().zip(&input[1]) {
+            lists.insert(Side::Left, *a);
+            lists.insert(Side::Right, *b);
+        }
+
+        assert_eq!(lists, IncrementalLists::from_lists(&input[0], &input[1]));
+        assert_eq!((lists.distance(), lists.similarity()), (11, 31));
+
+        // Sorted, the lists are now 1 2 3 3 3 4 and 3 3 4 5 9, leaving 4
+        // unpaired.
+        assert!(lists.remove(Side::Right, 3));
+        assert_eq!(lists.count(Side::Right, 3), 2);
+        assert_eq!(lists.similarity(), 31 - 3 * 3);
+        assert_eq!(lists.distance(), 2 + 1 + 1 + 2 + 6);
+
+        assert!(!lists.remove(Side::Left, 7));
+        assert_eq!(lists.len(Side::Left), 6);
+    }
+
+    #[test]
+    fn incremental_lists_extreme_ids() {
+        let (left, right) = (
+            [i64::MAX, i64::MAX, i64::MIN],
+            [i64::MAX, i64::MIN, i64::MIN],
+        );
+        let mut lists = IncrementalLists::from_lists(&left, &right);
+
+        assert_eq!(lists.similarity(), similarity_score(left, right));
+
+        lists.remove(Side::Right, i64::MIN);
+        lists.insert(Side::Left, i64::MAX);
+        assert_eq!(lists.similarity(), 3 * i64::MAX as i128 + i64::MIN as i128);
+    }
+
+    #[test]
+    fn explain_example() {
+        let input = input_gen(EXAMPLE).unwrap();
+        let explanation = explain_distance(&input[0], &input[1]);
+
+        let pairs = explanation
+            .pairs
+            .iter()
+            .map(|p| (p.left, p.right, p.distance))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            pairs,
+            [
+                (1, 3, 2),
+                (2, 3, 1),
+                (3, 3, 0),
+                (3, 4, 1),
+                (3, 5, 2),
+                (4, 9, 5)
+            ]
+        );
+
+        assert_eq!(explanation.total, solve_part1(&input));
+        assert_eq!(explanation.max_gap, Some(5));
+        assert_eq!(explanation.median_gap, Some(2));
+        assert_eq!(explanation.exact_matches, 1);
+        assert!(explanation.to_string().ends_with(
+            "total distance: 11\nmax gap:        5\nmedian gap:     2\nexact matches:  1"
+        ));
+    }
+
+    #[test]
+    fn explain_nothing() {
+        let explanation = explain_distance(&[], &[1]);
+
+        assert_eq!(explanation.pairs, []);
+        assert_eq!((explanation.max_gap, explanation.median_gap), (None, None));
+    }
+
+    #[test]
+    fn input_gen_empty() {
+        assert_eq!(input_gen("").unwrap(), Vec::<Vec<i64>>::new());
+        assert_eq!(solve_part1(&[]), 0);
+        assert_eq!(solve_part2(&[]), 0);
+    }
+
+    #[test]
+    fn input_gen_single_line() {
+        assert_eq!(input_gen("1   2").unwrap(), [vec![1], vec![2]]);
+    }
+
+    #[test]
+    fn input_gen_crlf_and_trailing_blank_lines() {
+        assert_eq!(
+            input_gen("3   4\r\n4   3\r\n\r\n").unwrap(),
+            [vec![3, 4], vec![4, 3]]
+        );
+    }
+
+    #[test]
+    fn input_gen_max_values() {
+        let input = input_gen(&format!("{}   0", i64::MAX)).unwrap();
+
+        assert_eq!(input, [vec![i64::MAX], vec![0]]);
+        assert_eq!(solve_part1(&input), i64::MAX);
+        assert!(input_gen("9223372036854775808   0").is_err());
+    }
+
+    #[test]
+    fn input_gen_rejects_wrong_column_count() {
+        let err = input_gen("1   2\n3").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 2));
+
+        let err = input_gen("1   2\n3   4   5").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 9));
+
+        let err = input_gen("1").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 2));
+    }
+
+    #[test]
+    fn input_gen_more_columns() {
+        let input = input_gen("1 2 3\n4 5 6\n").unwrap();
+
+        assert_eq!(input, [vec![1, 4], vec![2, 5], vec![3, 6]]);
+        assert_eq!(solve_part1(&input), 2);
+    }
+
+    #[test]
+    fn pair_matrices_example() {
+        let mut input = input_gen(EXAMPLE).unwrap();
+        input.push(vec![3, 3, 3]);
+
+        let matrices = pair_matrices(&input);
+
+        assert_eq!(matrices.distance, [[0, 11, 3], [11, 0, 0], [3, 0, 0]]);
+        assert_eq!(
+            matrices.similarity,
+            [[34, 31, 27], [31, 45, 27], [27, 27, 27]]
+        );
+
+        for (i, a) in input.iter().enumerate() {
+            for (j, b) in input.iter().enumerate() {
+                assert_eq!(matrices.distance[i][j], distance(a, b));
+                assert_eq!(
+                    matrices.similarity[i][j],
+                    similarity_score(a.iter().copied(), b.iter().copied())
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn pair_matrices_extreme_ids() {
+        let matrices = pair_matrices(&[vec![i64::MAX, i64::MAX], vec![i64::MAX]]);
+
+        assert_eq!(
+            matrices.similarity,
+            [
+                [4 * i64::MAX as i128, 2 * i64::MAX as i128],
+                [2 * i64::MAX as i128, i64::MAX as i128]
+            ]
+        );
+    }
+}
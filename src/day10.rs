@@ -2,39 +2,29 @@ use std::collections::HashSet;
 
 use aoc_runner_derive::{aoc, aoc_generator};
 
+use crate::{
+    geometry::Position,
+    grid::Grid,
+    parse::ParseError,
+    solution::{Answer, Solution, SolveError},
+};
+
 #[aoc_generator(day10)]
-pub fn input_gen(input: &str) -> Vec<Vec<u32>> {
-    input
-        .lines()
-        .map(|l| l.chars().map(|c| c.to_digit(10).unwrap()).collect())
-        .collect()
+pub fn input_gen(input: &str) -> Result<Grid<u32>, ParseError> {
+    Grid::parse_with(input, |c| c.to_digit(10)).map_err(|e| ParseError::from_grid(10, input, e))
 }
 
 struct Trail {
-    endings: Vec<(usize, usize)>,
+    endings: Vec<Position>,
 }
 
 impl Trail {
-    fn from_map(map: &[Vec<u32>], coords: (usize, usize)) -> Trail {
-        let height = map[coords.1][coords.0];
+    fn from_map(map: &Grid<u32>, coords: Position) -> Trail {
+        let height = map[coords];
         let endings = if height < 9 {
-            [(1, 0), (0, 1), (-1, 0), (0, -1)]
-                .into_iter()
-                .filter(|offset| coords.0 as i64 >= -offset.0 && coords.1 as i64 >= -offset.1)
-                .map(|offset| {
-                    (
-                        (coords.0 as i64 + offset.0) as usize,
-                        (coords.1 as i64 + offset.1) as usize,
-                    )
-                })
-                .filter(|coords| {
-                    coords.1 < map.len()
-                        && coords.0 < map[coords.1].len()
-                        && map[coords.1][coords.0] == height + 1
-                })
-                .map(|coords| Trail::from_map(map, coords))
-                .map(|c| c.endings.into_iter())
-                .flatten()
+            map.neighbours4(coords)
+                .filter(|(_, h)| **h == height + 1)
+                .flat_map(|(coords, _)| Trail::from_map(map, coords).endings)
                 .collect()
         } else {
             vec![coords]
@@ -45,37 +35,90 @@ impl Trail {
 }
 
 #[aoc(day10, part1)]
-pub fn solve_part1(input: &[Vec<u32>]) -> usize {
+pub fn solve_part1(input: &Grid<u32>) -> usize {
     input
         .iter()
-        .enumerate()
-        .map(|(y, l)| {
-            l.iter()
-                .enumerate()
-                .filter(|(_, h)| **h == 0)
-                .map(move |(x, _)| {
-                    Trail::from_map(input, (x, y))
-                        .endings
-                        .into_iter()
-                        .collect::<HashSet<_>>()
-                        .len()
-                })
+        .filter(|(_, h)| **h == 0)
+        .map(|(p, _)| {
+            Trail::from_map(input, p)
+                .endings
+                .into_iter()
+                .collect::<HashSet<_>>()
+                .len()
         })
-        .flatten()
         .sum()
 }
 
 #[aoc(day10, part2)]
-pub fn solve_part2(input: &[Vec<u32>]) -> usize {
+pub fn solve_part2(input: &Grid<u32>) -> usize {
     input
         .iter()
-        .enumerate()
-        .map(|(y, l)| {
-            l.iter()
-                .enumerate()
-                .filter(|(_, h)| **h == 0)
-                .map(move |(x, _)| Trail::from_map(input, (x, y)).endings.len())
-        })
-        .flatten()
+        .filter(|(_, h)| **h == 0)
+        .map(|(p, _)| Trail::from_map(input, p).endings.len())
         .sum()
 }
+
+pub struct Day10;
+
+impl Solution for Day10 {
+    const DAY: u8 = 10;
+
+    type Input = Grid<u32>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_gen(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(solve_part1(input).into())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(solve_part2(input).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+89010123
+78121874
+87430965
+96549874
+45678903
+32019012
+01329801
+10456732
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(solve_part1(&input_gen(EXAMPLE).unwrap()), 36);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(solve_part2(&input_gen(EXAMPLE).unwrap()), 81);
+    }
+
+    #[test]
+    fn input_gen_empty() {
+        assert!(input_gen("").is_err());
+    }
+
+    #[test]
+    fn input_gen_single_line() {
+        let input = input_gen("0123456789876543210").unwrap();
+
+        assert_eq!(solve_part1(&input), 2);
+        assert_eq!(solve_part2(&input), 2);
+    }
+
+    #[test]
+    fn input_gen_rejects_non_digits() {
+        let err = input_gen("0123\n45.7").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
+    }
+}
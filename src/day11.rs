@@ -2,12 +2,19 @@ use std::fmt::Debug;
 
 use aoc_runner_derive::{aoc, aoc_generator};
 
+use crate::{
+    parse::{lines, ParseError},
+    solution::{Answer, Solution, SolveError},
+};
+
 #[aoc_generator(day11)]
-pub fn input_gen(input: &str) -> Vec<usize> {
-    input
-        .split_whitespace()
-        .map(|n| n.parse::<usize>().unwrap())
-        .collect()
+pub fn input_gen(input: &str) -> Result<Vec<usize>, ParseError> {
+    let mut stones = Vec::new();
+    for l in lines(11, input) {
+        stones.extend(l.parse_fields::<usize>()?);
+    }
+
+    Ok(stones)
 }
 
 struct StoneIterator {
@@ -27,7 +34,7 @@ impl Iterator for StoneIterator {
         self.stones = self
             .stones
             .iter()
-            .map(|s| {
+            .flat_map(|s| {
                 if *s == 0 {
                     vec![1]
                 } else {
@@ -40,9 +47,7 @@ impl Iterator for StoneIterator {
                         vec![s * 2024]
                     }
                 }
-                .into_iter()
             })
-            .flatten()
             .collect();
 
         Some(self.stones.clone())
@@ -51,7 +56,18 @@ impl Iterator for StoneIterator {
 
 #[aoc(day11, part1)]
 pub fn solve_part1(input: &[usize]) -> usize {
-    StoneIterator::new(input.to_vec()).nth(24).unwrap().len()
+    count_stones_naive(input, 25)
+}
+
+/// Counts stones after `blinks` by simulating every single stone.
+pub(crate) fn count_stones_naive(stones: &[usize], blinks: usize) -> usize {
+    match blinks {
+        0 => stones.len(),
+        _ => StoneIterator::new(stones.to_vec())
+            .nth(blinks - 1)
+            .unwrap()
+            .len(),
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -73,7 +89,7 @@ impl Debug for State {
         let mut root = self;
         while let Some(ref next) = root.next {
             counts.push(next.count);
-            root = &*next;
+            root = next;
         }
 
         write!(f, "State {{ {counts:?} }}")
@@ -113,7 +129,7 @@ impl State {
         };
 
         if let Some(ref mut state) = self.next {
-            effects.extend(state.step().into_iter());
+            effects.extend(state.step());
 
             state.count += self.count;
         }
@@ -266,8 +282,7 @@ impl StoneCycler {
         let effects = self
             .states
             .iter_mut()
-            .map(|s| s.step())
-            .flatten()
+            .flat_map(|s| s.step())
             .collect::<Vec<Effect>>();
 
         effects.into_iter().for_each(|e| match e {
@@ -283,10 +298,16 @@ impl StoneCycler {
 
 #[aoc(day11, part2)]
 pub fn solve_part2(input: &[usize]) -> usize {
-    let mut stones = input.to_vec();
+    count_stones(input, 75)
+}
+
+/// Counts stones after `blinks`, handing single-digit stones to a
+/// [`StoneCycler`] instead of simulating them.
+pub(crate) fn count_stones(stones: &[usize], blinks: usize) -> usize {
+    let mut stones = stones.to_vec();
     let mut cycler = StoneCycler::new();
 
-    (0..75).for_each(|_| {
+    (0..blinks).for_each(|_| {
         stones = StoneIterator::new(stones.clone()).next().unwrap();
         cycler.step();
 
@@ -299,3 +320,68 @@ pub fn solve_part2(input: &[usize]) -> usize {
 
     stones.len() + cycler.count()
 }
+
+pub struct Day11;
+
+impl Solution for Day11 {
+    const DAY: u8 = 11;
+
+    type Input = Vec<usize>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_gen(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(solve_part1(input).into())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(solve_part2(input).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(solve_part1(&input_gen("125 17").unwrap()), 55312);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(solve_part2(&input_gen("125 17").unwrap()), 65601038650482);
+    }
+
+    #[test]
+    fn blink_rules() {
+        let mut stones = StoneIterator::new(vec![0, 1, 10, 99, 999]);
+
+        assert_eq!(stones.next().unwrap(), vec![1, 2024, 1, 0, 9, 9, 2021976]);
+    }
+
+    #[test]
+    fn input_gen_empty() {
+        let input = input_gen("").unwrap();
+
+        assert!(input.is_empty());
+        assert_eq!(solve_part2(&input), 0);
+    }
+
+    #[test]
+    fn input_gen_max_values() {
+        assert_eq!(
+            input_gen(&usize::MAX.to_string()).unwrap(),
+            vec![usize::MAX]
+        );
+        assert!(input_gen("18446744073709551616").is_err());
+    }
+
+    #[test]
+    fn input_gen_rejects_non_numbers() {
+        let err = input_gen("125 1x7").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 5));
+    }
+}
@@ -1,61 +1,923 @@
+use std::{cmp::Reverse, fmt, ops::Range};
+
 use aoc_runner_derive::{aoc, aoc_generator};
 
+use crate::{
+    parse::{lines, ParseError},
+    solution::{Answer, Solution, SolveError},
+};
+
 #[aoc_generator(day2)]
-pub fn input_gen(input: &str) -> Vec<Vec<i64>> {
-    input
-        .lines()
+pub fn input_gen(input: &str) -> Result<Vec<Vec<i64>>, ParseError> {
+    lines(2, input)
         .map(|l| {
-            l.split_whitespace()
-                .map(|d| d.parse::<i64>().unwrap())
-                .collect()
+            let report = l.parse_fields()?;
+
+            if report.is_empty() {
+                Err(l.error_at_end("expected at least one level"))
+            } else {
+                Ok(report)
+            }
         })
         .collect()
 }
 
-fn is_report_valid(report: &[i64]) -> bool {
-    let increasing = report[1] > report[0];
+/// Which way the levels of a safe report have to go.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    Increasing,
+    Decreasing,
+    /// Either way, as set by the first two different levels.
+    Either,
+}
+
+/// What makes a report safe. The default is the puzzle's: strictly
+/// increasing or decreasing, in steps of 1 to 3.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SafetyRules {
+    /// The smallest allowed difference between two different levels.
+    pub min_step: i64,
+    /// The largest allowed difference between two levels.
+    pub max_step: i64,
+    /// Whether two adjacent levels may be equal, whatever `min_step` is.
+    pub allow_plateaus: bool,
+    pub direction: Direction,
+}
+
+impl Default for SafetyRules {
+    fn default() -> Self {
+        Self {
+            min_step: 1,
+            max_step: 3,
+            allow_plateaus: false,
+            direction: Direction::Either,
+        }
+    }
+}
+
+impl SafetyRules {
+    /// The rule broken by a step of `diff`, given the direction the report
+    /// is going (`None` if it has not been set yet).
+    fn check_step(&self, diff: i64, increasing: Option<bool>) -> Option<Rule> {
+        if diff == 0 {
+            return (!self.allow_plateaus).then_some(Rule::StepTooSmall);
+        }
+
+        let wrong_way = match (self.direction, increasing) {
+            (Direction::Increasing, _) => diff < 0,
+            (Direction::Decreasing, _) => diff > 0,
+            (Direction::Either, Some(increasing)) => (diff > 0) != increasing,
+            (Direction::Either, None) => false,
+        };
+
+        if wrong_way {
+            Some(Rule::DirectionChange)
+        } else if diff.unsigned_abs() < self.min_step.max(1) as u64 {
+            Some(Rule::StepTooSmall)
+        } else if diff.unsigned_abs() > self.max_step.max(0) as u64 {
+            Some(Rule::StepTooLarge)
+        } else {
+            None
+        }
+    }
+
+    /// The signs of the steps a safe report can take, 1 going up.
+    fn signs(&self) -> &'static [i64] {
+        match self.direction {
+            Direction::Increasing => &[1],
+            Direction::Decreasing => &[-1],
+            Direction::Either => &[1, -1],
+        }
+    }
+}
+
+/// A safety rule a pair of adjacent levels can break.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rule {
+    /// The levels go the other way than required, or than the first two
+    /// different levels did.
+    DirectionChange,
+    /// The levels are equal, or closer than the minimum step.
+    StepTooSmall,
+    /// The levels differ by more than the maximum step.
+    StepTooLarge,
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Rule::DirectionChange => write!(f, "direction change"),
+            Rule::StepTooSmall => write!(f, "step too small"),
+            Rule::StepTooLarge => write!(f, "step too large"),
+        }
+    }
+}
+
+/// The first level that breaks a rule, compared to the level before it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Violation {
+    pub index: usize,
+    pub rule: Rule,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportVerdict {
+    Safe,
+    Unsafe {
+        violation: Violation,
+        /// The first level whose removal makes the report safe, if any.
+        dampened: Option<usize>,
+    },
+}
+
+impl ReportVerdict {
+    pub fn is_safe(&self) -> bool {
+        matches!(self, ReportVerdict::Safe)
+    }
+
+    /// Whether the report is safe with at most one level removed.
+    pub fn is_dampened_safe(&self) -> bool {
+        matches!(
+            self,
+            ReportVerdict::Safe
+                | ReportVerdict::Unsafe {
+                    dampened: Some(_),
+                    ..
+                }
+        )
+    }
+}
+
+fn first_violation(
+    mut levels: impl Iterator<Item = i64>,
+    rules: &SafetyRules,
+) -> Option<Violation> {
+    let mut prev = levels.next()?;
+    let mut increasing = None;
+
+    for (index, level) in (1..).zip(levels) {
+        let diff = level.saturating_sub(prev);
+
+        if let Some(rule) = rules.check_step(diff, increasing) {
+            return Some(Violation { index, rule });
+        }
+
+        if diff != 0 {
+            increasing.get_or_insert(diff > 0);
+        }
+        prev = level;
+    }
+
+    None
+}
+
+pub fn is_report_valid(report: &[i64], rules: &SafetyRules) -> bool {
+    first_violation(report.iter().copied(), rules).is_none()
+}
+
+/// A safe version of a report and how many of its levels were changed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Repair {
+    pub changes: usize,
+    pub report: Vec<i64>,
+}
+
+impl SafetyRules {
+    /// How many of `gap` steps have to be non-zero to go up by `rise`, the
+    /// fewest that work, or `None` if no allowed steps add up to `rise`.
+    fn nonzero_steps(&self, rise: i128, gap: i128) -> Option<i128> {
+        let (min, max) = (self.min_step.max(1) as i128, self.max_step as i128);
+
+        if rise < 0 || min > max {
+            return (rise == 0 && self.allow_plateaus).then_some(0);
+        }
+
+        let fewest = if self.allow_plateaus { 0 } else { gap };
+        let fewest = fewest.max((rise + max - 1) / max);
+        let most = gap.min(rise / min);
+
+        (fewest <= most).then_some(fewest)
+    }
+
+    /// A step that is always allowed, if there is one.
+    fn any_step(&self) -> Option<i64> {
+        if self.min_step.max(1) <= self.max_step {
+            Some(self.min_step.max(1))
+        } else {
+            self.allow_plateaus.then_some(0)
+        }
+    }
+}
+
+/// Changes as few levels of `report` as possible to make it safe, or
+/// returns `None` if no report of that length can be safe under `rules`
+/// with levels that fit in an `i64`.
+///
+/// Whatever levels are left unchanged must be reachable from one another
+/// with allowed steps, so this looks for the longest chain of such levels,
+/// in quadratic time, then fills in the others. A chain can only start or
+/// end where there is room for the levels before or after it. If no level
+/// can stay, the repair starts from `i64::MIN` (or `i64::MAX` going down)
+/// and changes every level.
+pub fn repair_report(report: &[i64], rules: &SafetyRules) -> Option<Repair> {
+    if report.len() < 2 {
+        return Some(Repair {
+            changes: 0,
+            report: report.to_vec(),
+        });
+    }
+
+    let step = rules.any_step()?;
+
+    // For each sign, the longest chain ending at every level and the level
+    // before it in the chain. Ties go to the first sign and earliest end.
+    let mut best = None;
+    let mut best_len = 0;
 
-    report
-        .windows(2)
-        .find(|levels| {
-            let diff = (levels[0] - levels[1]).abs();
+    for sign in rules.signs() {
+        // Whether `count` levels, `step` apart, fit after (or before, for a
+        // negative count) the level at `idx`.
+        // With plateaus, flat levels always fit.
+        let room = |idx: usize, count: i128| {
+            let last = report[idx] as i128 + *sign as i128 * step as i128 * count;
+            rules.allow_plateaus || i64::try_from(last).is_ok()
+        };
 
-            !(diff <= 3
-                && diff >= 1
-                && if increasing {
-                    levels[1] > levels[0]
-                } else {
-                    levels[1] < levels[0]
-                })
+        let mut longest = (0..report.len())
+            .map(|j| (usize::from(room(j, -(j as i128))), None))
+            .collect::<Vec<_>>();
+
+        for j in 0..report.len() {
+            for i in 0..j {
+                let rise = (report[j] as i128 - report[i] as i128) * *sign as i128;
+
+                if longest[i].0 > 0
+                    && longest[i].0 + 1 > longest[j].0
+                    && rules.nonzero_steps(rise, (j - i) as i128).is_some()
+                {
+                    longest[j] = (longest[i].0 + 1, Some(i));
+                }
+            }
+        }
+
+        let end = (0..report.len())
+            .filter(|end| room(*end, (report.len() - 1 - end) as i128))
+            .max_by_key(|end| (longest[*end].0, Reverse(*end)));
+        let Some(end) = end else {
+            continue;
+        };
+
+        if longest[end].0 > best_len {
+            best_len = longest[end].0;
+            best = Some((*sign, longest, end));
+        }
+    }
+
+    let Some((sign, longest, end)) = best else {
+        return fresh_report(report, rules.signs()[0], step);
+    };
+
+    let mut kept = vec![end];
+    while let Some(prev) = longest[*kept.last().unwrap()].1 {
+        kept.push(prev);
+    }
+    kept.reverse();
+
+    let mut repaired = report.to_vec();
+
+    // Steps that would leave the `i64` range are flat instead, which only
+    // happens when plateaus are allowed.
+    for idx in (0..kept[0]).rev() {
+        let next = repaired[idx + 1];
+        repaired[idx] = next.checked_sub(sign * step).unwrap_or(next);
+    }
+    for idx in kept[kept.len() - 1] + 1..report.len() {
+        let prev = repaired[idx - 1];
+        repaired[idx] = prev.checked_add(sign * step).unwrap_or(prev);
+    }
+
+    for pair in kept.windows(2) {
+        let (i, j) = (pair[0], pair[1]);
+        let rise = (report[j] as i128 - report[i] as i128) * sign as i128;
+        let nonzero = rules.nonzero_steps(rise, (j - i) as i128)?;
+
+        // Spread the rise evenly over the first steps, the rest are flat.
+        // Every level in between lies between the two kept ones.
+        for (n, idx) in (i + 1..j).enumerate() {
+            let n = n as i128;
+            let step = if n < nonzero {
+                rise / nonzero + i128::from(n < rise % nonzero)
+            } else {
+                0
+            };
+
+            repaired[idx] = (repaired[idx - 1] as i128 + sign as i128 * step) as i64;
+        }
+    }
+
+    Some(Repair {
+        changes: report.len() - kept.len(),
+        report: repaired,
+    })
+}
+
+/// The levels of a report going `sign * step` at a time from the far end
+/// of the `i64` range, if they all fit, as a repair of `report`.
+fn fresh_report(report: &[i64], sign: i64, step: i64) -> Option<Repair> {
+    let first = if sign > 0 { i64::MIN } else { i64::MAX };
+    let repaired = (0..report.len())
+        .map(|idx| {
+            let level = first as i128 + sign as i128 * step as i128 * idx as i128;
+            i64::try_from(level).ok()
         })
-        .is_none()
+        .collect::<Option<Vec<_>>>()?;
+
+    Some(Repair {
+        changes: report.iter().zip(&repaired).filter(|(a, b)| a != b).count(),
+        report: repaired,
+    })
 }
 
-#[aoc(day2, part1)]
-pub fn solve_part1(input: &[Vec<i64>]) -> usize {
+fn is_valid_without(report: &[i64], skip: usize, rules: &SafetyRules) -> bool {
+    let levels = report
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| *idx != skip)
+        .map(|(_, level)| *level);
+
+    first_violation(levels, rules).is_none()
+}
+
+/// Why `report` is unsafe, if it is, and which level the Problem Dampener
+/// would remove.
+pub fn report_verdict(report: &[i64], rules: &SafetyRules) -> ReportVerdict {
+    let Some(violation) = first_violation(report.iter().copied(), rules) else {
+        return ReportVerdict::Safe;
+    };
+
+    // Removing a level after the violation keeps it. Removing one before
+    // the level ahead of it keeps it too, unless that changes the direction
+    // set by the first two different levels, at `turn` and the one before.
+    // Every level before `turn` is equal, so removing the first of them is
+    // the same as removing any other. That leaves four candidates.
+    let turn = (1..violation.index)
+        .find(|idx| report[*idx] != report[idx - 1])
+        .unwrap_or(violation.index);
+
+    let mut candidates = [0, turn, violation.index - 1, violation.index];
+    candidates.sort();
+
+    let dampened = candidates
+        .into_iter()
+        .find(|skip| is_valid_without(report, *skip, rules));
+
+    ReportVerdict::Unsafe {
+        violation,
+        dampened,
+    }
+}
+
+/// `report` with the violating level in brackets, followed by the rule it
+/// breaks and the dampener's fix.
+pub fn annotate(report: &[i64], verdict: &ReportVerdict) -> String {
+    let ReportVerdict::Unsafe {
+        violation,
+        dampened,
+    } = verdict
+    else {
+        let levels = report.iter().map(i64::to_string).collect::<Vec<_>>();
+        return format!("{}: safe", levels.join(" "));
+    };
+
+    let levels = report
+        .iter()
+        .enumerate()
+        .map(|(idx, level)| {
+            if idx == violation.index {
+                format!("[{level}]")
+            } else {
+                level.to_string()
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let fix = match dampened {
+        Some(idx) => format!("safe without level {idx} ({})", report[*idx]),
+        None => "no single removal makes it safe".to_string(),
+    };
+
+    format!(
+        "{}: {} at level {}, {fix}",
+        levels.join(" "),
+        violation.rule,
+        violation.index
+    )
+}
+
+/// Maxima over ranges of a fixed number of slots, all zero to begin with.
+struct RangeMax {
+    tree: Vec<usize>,
+}
+
+impl RangeMax {
+    fn new(len: usize) -> Self {
+        Self {
+            tree: vec![0; 2 * len],
+        }
+    }
+
+    /// Raises slot `idx` to at least `value`.
+    fn raise(&mut self, idx: usize, value: usize) {
+        let mut node = idx + self.tree.len() / 2;
+        while node > 0 && self.tree[node] < value {
+            self.tree[node] = value;
+            node /= 2;
+        }
+    }
+
+    /// The largest value in the slots of `range`, zero if it is empty.
+    fn max(&self, range: Range<usize>) -> usize {
+        let len = self.tree.len() / 2;
+        let (mut low, mut high) = (range.start + len, range.end + len);
+        let mut max = 0;
+
+        while low < high {
+            if low % 2 == 1 {
+                max = max.max(self.tree[low]);
+                low += 1;
+            }
+            if high % 2 == 1 {
+                high -= 1;
+                max = max.max(self.tree[high]);
+            }
+            low /= 2;
+            high /= 2;
+        }
+
+        max
+    }
+}
+
+/// The fewest levels to remove from `report` to make it safe.
+///
+/// What is left after the removals is a subsequence going up (or down) in
+/// allowed steps, so this finds the longest one. For each level, `longest`
+/// holds the longest such subsequence ending at an earlier level of each
+/// value, indexed by the rank of the value in the report. Extending one is
+/// a range maximum over the values an allowed step away, so this takes
+/// O(n log n) time however wide the step range is.
+pub fn min_removals(report: &[i64], rules: &SafetyRules) -> usize {
+    let (min, max) = (rules.min_step.max(1) as i128, rules.max_step as i128);
+
+    let mut values = report.to_vec();
+    values.sort_unstable();
+    values.dedup();
+    let rank = |level: i128| values.partition_point(|v| (*v as i128) < level);
+
+    let longest_monotonic = |sign: i128| {
+        let mut longest = RangeMax::new(values.len());
+        let mut best = 0;
+
+        for level in report {
+            let level = *level as i128;
+            let at = rank(level);
+
+            // Earlier levels `sign * step` below this one.
+            let (near, far) = (level - sign * min, level - sign * max);
+            let stepped = if min <= max {
+                longest.max(rank(near.min(far))..rank(near.max(far) + 1))
+            } else {
+                0
+            };
+            let flat = if rules.allow_plateaus {
+                longest.max(at..at + 1)
+            } else {
+                0
+            };
+
+            let len = 1 + stepped.max(flat);
+            longest.raise(at, len);
+            best = best.max(len);
+        }
+
+        best
+    };
+
+    let longest = rules
+        .signs()
+        .iter()
+        .map(|sign| longest_monotonic(*sign as i128))
+        .max()
+        .unwrap_or_default();
+
+    report.len() - longest
+}
+
+/// Whether the Problem Dampener can make `report` safe by removing at most
+/// `tolerance` levels.
+pub fn is_dampened_safe(report: &[i64], rules: &SafetyRules, tolerance: usize) -> bool {
+    min_removals(report, rules) <= tolerance
+}
+
+/// [`is_dampened_safe`] by trying every removal.
+pub(crate) fn is_dampened_safe_naive(
+    report: &[i64],
+    rules: &SafetyRules,
+    tolerance: usize,
+) -> bool {
+    is_report_valid(report, rules)
+        || tolerance > 0
+            && (0..report.len()).any(|skip| {
+                let mut report = report.to_vec();
+                report.remove(skip);
+
+                is_dampened_safe_naive(&report, rules, tolerance - 1)
+            })
+}
+
+/// The number of reports that are safe under `rules`.
+pub fn count_safe(input: &[Vec<i64>], rules: &SafetyRules) -> usize {
     input
         .iter()
-        .filter(|report| is_report_valid(report))
+        .filter(|report| is_report_valid(report, rules))
         .count()
 }
 
-#[aoc(day2, part2)]
-pub fn solve_part2(input: &[Vec<i64>]) -> usize {
+/// The number of reports that are safe under `rules` once the Problem
+/// Dampener removes at most `tolerance` levels.
+pub fn count_dampened_safe(input: &[Vec<i64>], rules: &SafetyRules, tolerance: usize) -> usize {
     input
-        .into_iter()
-        .filter(|report| {
-            (-1..report.len() as i64)
-                .find(|n| {
-                    let report = report
-                        .iter()
-                        .enumerate()
-                        .filter(|(i, _)| *i as i64 != *n)
-                        .map(|(_, l)| *l)
-                        .collect::<Vec<i64>>();
-
-                    is_report_valid(&report)
-                })
-                .is_some()
-        })
+        .iter()
+        .filter(|report| is_dampened_safe(report, rules, tolerance))
         .count()
 }
+
+#[aoc(day2, part1)]
+pub fn solve_part1(input: &[Vec<i64>]) -> usize {
+    count_safe(input, &SafetyRules::default())
+}
+
+#[aoc(day2, part2)]
+pub fn solve_part2(input: &[Vec<i64>]) -> usize {
+    count_dampened_safe(input, &SafetyRules::default(), 1)
+}
+
+pub struct Day2;
+
+impl Solution for Day2 {
+    const DAY: u8 = 2;
+
+    type Input = Vec<Vec<i64>>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_gen(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(solve_part1(input).into())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(solve_part2(input).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RULES: SafetyRules = SafetyRules {
+        min_step: 1,
+        max_step: 3,
+        allow_plateaus: false,
+        direction: Direction::Either,
+    };
+
+    const EXAMPLE: &str = "\
+7 6 4 2 1
+1 2 7 8 9
+9 7 6 2 1
+1 3 2 4 5
+8 6 4 4 1
+1 3 6 7 9
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(solve_part1(&input_gen(EXAMPLE).unwrap()), 2);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(solve_part2(&input_gen(EXAMPLE).unwrap()), 4);
+    }
+
+    #[test]
+    fn report_validity() {
+        assert!(is_report_valid(&[7, 6, 4, 2, 1], &RULES));
+        assert!(is_report_valid(&[1, 3, 6, 7, 9], &RULES));
+        assert!(!is_report_valid(&[1, 2, 7, 8, 9], &RULES));
+        assert!(!is_report_valid(&[1, 3, 2, 4, 5], &RULES));
+        assert!(!is_report_valid(&[8, 6, 4, 4, 1], &RULES));
+        assert!(is_report_valid(&[5], &RULES));
+        assert!(is_report_valid(&[], &RULES));
+    }
+
+    #[test]
+    fn example_verdicts() {
+        let unsafe_report = |index, rule, dampened| ReportVerdict::Unsafe {
+            violation: Violation { index, rule },
+            dampened,
+        };
+
+        let verdicts = input_gen(EXAMPLE)
+            .unwrap()
+            .iter()
+            .map(|report| report_verdict(report, &RULES))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            verdicts,
+            [
+                ReportVerdict::Safe,
+                unsafe_report(2, Rule::StepTooLarge, None),
+                unsafe_report(3, Rule::StepTooLarge, None),
+                unsafe_report(2, Rule::DirectionChange, Some(1)),
+                unsafe_report(3, Rule::StepTooSmall, Some(2)),
+                ReportVerdict::Safe,
+            ]
+        );
+    }
+
+    #[test]
+    fn verdict_first_step() {
+        let verdict = report_verdict(&[5, 5, 6, 7], &RULES);
+
+        assert_eq!(
+            verdict,
+            ReportVerdict::Unsafe {
+                violation: Violation {
+                    index: 1,
+                    rule: Rule::StepTooSmall
+                },
+                dampened: Some(0),
+            }
+        );
+        assert_eq!(
+            annotate(&[5, 5, 6, 7], &verdict),
+            "5 [5] 6 7: step too small at level 1, safe without level 0 (5)"
+        );
+    }
+
+    #[test]
+    fn removals() {
+        assert_eq!(min_removals(&[], &RULES), 0);
+        assert_eq!(min_removals(&[4], &RULES), 0);
+        assert_eq!(min_removals(&[1, 3, 2, 4, 5], &RULES), 1);
+        assert_eq!(min_removals(&[1, 9, 2, 9, 3, 9, 4], &RULES), 3);
+        assert_eq!(min_removals(&[9, 9, 9, 9], &RULES), 3);
+        assert_eq!(min_removals(&[i64::MIN, i64::MIN + 1, i64::MAX], &RULES), 1);
+
+        assert!(!is_dampened_safe(&[1, 9, 2, 9, 3, 9, 4], &RULES, 2));
+        assert!(is_dampened_safe(&[1, 9, 2, 9, 3, 9, 4], &RULES, 3));
+    }
+
+    #[test]
+    fn removals_with_wide_steps() {
+        let wide = SafetyRules {
+            max_step: i64::MAX,
+            ..RULES
+        };
+        assert_eq!(min_removals(&[1, 1_000_000, 5, 1 << 40, 7], &wide), 2);
+        assert_eq!(min_removals(&[i64::MIN + 1, 0, i64::MAX, 0], &wide), 1);
+        assert_eq!(min_removals(&[i64::MAX, i64::MIN, i64::MAX], &wide), 2);
+
+        let huge = SafetyRules {
+            min_step: i64::MAX,
+            max_step: i64::MAX,
+            ..RULES
+        };
+        assert_eq!(min_removals(&[-1, i64::MAX, 0, i64::MIN], &huge), 2);
+
+        let rising = (0..20_000).collect::<Vec<_>>();
+        assert_eq!(min_removals(&rising, &wide), 0);
+    }
+
+    #[test]
+    fn default_rules() {
+        assert_eq!(SafetyRules::default(), RULES);
+    }
+
+    #[test]
+    fn custom_rules() {
+        let rules = SafetyRules {
+            min_step: 2,
+            max_step: 5,
+            allow_plateaus: true,
+            direction: Direction::Increasing,
+        };
+
+        assert!(is_report_valid(&[1, 1, 3, 8, 8], &rules));
+        assert!(!is_report_valid(&[1, 2], &rules));
+        assert!(!is_report_valid(&[1, 7], &rules));
+
+        let verdict = report_verdict(&[5, 5, 3, 8], &rules);
+        assert_eq!(
+            verdict,
+            ReportVerdict::Unsafe {
+                violation: Violation {
+                    index: 2,
+                    rule: Rule::DirectionChange
+                },
+                dampened: Some(2),
+            }
+        );
+
+        let input = input_gen(EXAMPLE).unwrap();
+        assert_eq!(count_safe(&input, &rules), 0);
+        assert_eq!(count_dampened_safe(&input, &rules, 1), 1);
+        assert_eq!(count_dampened_safe(&input, &rules, 2), 3);
+    }
+
+    #[test]
+    fn plateaus_set_no_direction() {
+        let rules = SafetyRules {
+            allow_plateaus: true,
+            ..RULES
+        };
+
+        assert!(is_report_valid(&[4, 4, 2, 2, 1], &rules));
+        assert_eq!(min_removals(&[4, 4, 5, 3, 2], &rules), 1);
+        assert_eq!(
+            report_verdict(&[4, 4, 5, 3, 2], &rules),
+            ReportVerdict::Unsafe {
+                violation: Violation {
+                    index: 3,
+                    rule: Rule::DirectionChange
+                },
+                dampened: Some(2),
+            }
+        );
+    }
+
+    #[test]
+    fn repair_examples() {
+        let repairs = input_gen(EXAMPLE)
+            .unwrap()
+            .iter()
+            .map(|report| repair_report(report, &RULES).unwrap())
+            .collect::<Vec<_>>();
+
+        let changes = repairs.iter().map(|r| r.changes).collect::<Vec<_>>();
+        assert_eq!(changes, [0, 1, 1, 2, 1, 0]);
+
+        assert_eq!(repairs[0].report, [7, 6, 4, 2, 1]);
+        assert_eq!(repairs[2].report, [9, 7, 6, 3, 1]);
+        assert_eq!(repairs[4].report, [8, 6, 4, 2, 1]);
+
+        for (report, repair) in input_gen(EXAMPLE).unwrap().iter().zip(&repairs) {
+            assert!(is_report_valid(&repair.report, &RULES));
+
+            let changed = report.iter().zip(&repair.report).filter(|(a, b)| a != b);
+            assert_eq!(changed.count(), repair.changes);
+        }
+    }
+
+    #[test]
+    fn repair_generated_reports() {
+        let input = crate::synth::day2(
+            &mut crate::rng::Rng::new(2),
+            &crate::synth::Params {
+                size: 200,
+                density: 0.6,
+            },
+        );
+
+        for report in input_gen(&input).unwrap() {
+            let repair = repair_report(&report, &RULES).unwrap();
+            let changed = report.iter().zip(&repair.report).filter(|(a, b)| a != b);
+
+            assert!(is_report_valid(&repair.report, &RULES), "{report:?}");
+            assert_eq!(changed.count(), repair.changes, "{report:?}");
+            assert_eq!(
+                repair.changes == 0,
+                is_report_valid(&report, &RULES),
+                "{report:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn repair_with_rules() {
+        let flat = SafetyRules {
+            allow_plateaus: true,
+            ..RULES
+        };
+        let repair = repair_report(&[1, 9, 1, 1, 2], &flat).unwrap();
+        assert_eq!((repair.changes, repair.report), (1, vec![1, 1, 1, 1, 2]));
+
+        let impossible = SafetyRules {
+            min_step: 4,
+            ..RULES
+        };
+        assert_eq!(repair_report(&[1, 2], &impossible), None);
+        assert_eq!(repair_report(&[1], &impossible).unwrap().changes, 0);
+
+        let even = SafetyRules {
+            min_step: 2,
+            max_step: 2,
+            allow_plateaus: true,
+            direction: Direction::Decreasing,
+        };
+        let repair = repair_report(&[9, 8, 5, 5, 3], &even).unwrap();
+        assert_eq!((repair.changes, repair.report), (1, vec![9, 7, 5, 5, 3]));
+    }
+
+    #[test]
+    fn repair_extreme_levels() {
+        let repair = repair_report(&[i64::MAX, 0], &RULES).unwrap();
+        assert_eq!((repair.changes, repair.report), (1, vec![-1, 0]));
+
+        let repair = repair_report(&[i64::MIN, i64::MAX, i64::MAX], &RULES).unwrap();
+        assert_eq!(repair.changes, 2);
+        assert!(is_report_valid(&repair.report, &RULES));
+
+        let increasing = SafetyRules {
+            direction: Direction::Increasing,
+            ..RULES
+        };
+        let repair = repair_report(&[i64::MAX - 1, i64::MAX, 5], &increasing).unwrap();
+        assert_eq!((repair.changes, repair.report), (2, vec![3, 4, 5]));
+
+        let huge = SafetyRules {
+            min_step: i64::MAX,
+            max_step: i64::MAX,
+            ..increasing
+        };
+        assert_eq!(repair_report(&[0, 0, 0, 0], &huge), None);
+        assert_eq!(
+            repair_report(&[i64::MIN, 5], &huge).unwrap().report,
+            [i64::MIN, -1]
+        );
+
+        // No level can stay, as none has room for two huge steps on the
+        // side it needs them.
+        let repair = repair_report(&[i64::MAX, i64::MIN, 0], &huge).unwrap();
+        assert_eq!(
+            (repair.changes, repair.report),
+            (3, vec![i64::MIN, -1, i64::MAX - 1])
+        );
+
+        let decreasing = SafetyRules {
+            direction: Direction::Decreasing,
+            ..huge
+        };
+        let repair = repair_report(&[i64::MIN, i64::MAX, 0], &decreasing).unwrap();
+        assert_eq!(
+            (repair.changes, repair.report),
+            (3, vec![i64::MAX, 0, -i64::MAX])
+        );
+
+        let flat_huge = SafetyRules {
+            allow_plateaus: true,
+            ..huge
+        };
+        let repair = repair_report(&[i64::MAX, i64::MIN, 0, 0], &flat_huge).unwrap();
+        assert_eq!(repair.changes, 2);
+        assert!(is_report_valid(&repair.report, &flat_huge));
+    }
+
+    #[test]
+    fn input_gen_empty() {
+        assert!(input_gen("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn input_gen_single_line() {
+        let input = input_gen("1 2 3").unwrap();
+
+        assert_eq!(input, vec![vec![1, 2, 3]]);
+        assert_eq!(solve_part1(&input), 1);
+    }
+
+    #[test]
+    fn input_gen_single_level() {
+        let input = input_gen("42").unwrap();
+
+        assert_eq!(solve_part1(&input), 1);
+        assert_eq!(solve_part2(&input), 1);
+    }
+
+    #[test]
+    fn input_gen_max_values() {
+        let input = input_gen(&format!("{} {}", i64::MAX - 1, i64::MAX)).unwrap();
+
+        assert_eq!(solve_part1(&input), 1);
+    }
+
+    #[test]
+    fn input_gen_rejects_blank_report() {
+        let err = input_gen("1 2\n\n3 4").unwrap_err();
+        assert_eq!(err.line, 2);
+    }
+}
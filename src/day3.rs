@@ -1,17 +1,22 @@
+use std::{
+    fmt,
+    io::{self, Read},
+    ops::Range,
+    str::FromStr,
+};
+
 use aoc_runner_derive::{aoc, aoc_generator};
 
+use crate::{
+    parse::ParseError,
+    solution::{Answer, Solution, SolveError},
+};
+
 #[aoc_generator(day3)]
 pub fn input_gen(input: &str) -> String {
     input.to_string()
 }
 
-fn peek_until<'a>(input: &'a str, token: &str) -> Result<&'a str, &'a str> {
-    input
-        .find(token)
-        .map(|offset| &input[offset..])
-        .ok_or(&input[input.len()..])
-}
-
 fn peek_until_first<'a>(input: &'a str, tokens: &[&str]) -> Result<(&'a str, usize), &'a str> {
     tokens
         .iter()
@@ -23,83 +28,1001 @@ fn peek_until_first<'a>(input: &'a str, tokens: &[&str]) -> Result<(&'a str, usi
 }
 
 fn consume<'a>(input: &'a str, token: &str) -> Result<&'a str, &'a str> {
-    if input.starts_with(token) {
-        Ok(&input[token.len()..])
-    } else {
-        Err(input)
+    input.strip_prefix(token).ok_or(input)
+}
+
+/// An operand: one to three ASCII digits.
+fn parse_number(input: &str) -> Result<(&str, i64), Rejected<'_>> {
+    const MAX_DIGITS: usize = 3;
+
+    let digits = input.bytes().take_while(u8::is_ascii_digit).count();
+
+    match digits {
+        0 => Err(reject(input, Reason::NonDigit)),
+        1..=MAX_DIGITS => Ok((&input[digits..], input[..digits].parse().unwrap())),
+        _ => Err((&input[MAX_DIGITS..], Reason::TooManyDigits)),
     }
 }
 
-fn parse_number(input: &str) -> Result<(&str, i64), &str> {
-    let digits = input
-        .chars()
-        .scan((), |_, c| if c.is_numeric() { Some(()) } else { None })
-        .count();
+/// Why a candidate instruction is not well formed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    /// No `(` after the name, or no `)` after the last operand.
+    MissingParen,
+    /// An operand does not start with an ASCII digit.
+    NonDigit,
+    /// An operand has more than three digits.
+    TooManyDigits,
+    /// Operands are not separated by a `,`.
+    BadSeparator,
+    /// The memory ends in the middle of the instruction.
+    Truncated,
+}
+
+impl fmt::Display for Reason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reason::MissingParen => write!(f, "missing paren"),
+            Reason::NonDigit => write!(f, "non-digit operand"),
+            Reason::TooManyDigits => write!(f, "too many digits"),
+            Reason::BadSeparator => write!(f, "bad separator"),
+            Reason::Truncated => write!(f, "truncated"),
+        }
+    }
+}
 
-    if digits > 0 {
-        let n = &input[0..digits].parse::<i64>().unwrap();
+/// Where parsing stopped, and why.
+type Rejected<'a> = (&'a str, Reason);
 
-        Ok((&input[digits..], *n))
+fn reject(rest: &str, reason: Reason) -> Rejected<'_> {
+    if rest.is_empty() {
+        (rest, Reason::Truncated)
     } else {
-        Err(input)
+        (rest, reason)
+    }
+}
+
+/// `pattern` (an instruction name and its opening parenthesis) followed by
+/// `arity` comma separated numbers and a closing parenthesis.
+fn parse_call<'a>(
+    input: &'a str,
+    pattern: &str,
+    arity: usize,
+) -> Result<(&'a str, Vec<i64>), Rejected<'a>> {
+    let mut input = consume(input, pattern).map_err(|rest| reject(rest, Reason::MissingParen))?;
+    let mut args = Vec::with_capacity(arity);
+
+    for idx in 0..arity {
+        if idx > 0 {
+            input = consume(input, ",").map_err(|rest| reject(rest, Reason::BadSeparator))?;
+        }
+
+        let (rest, n) = parse_number(input)?;
+        input = rest;
+        args.push(n);
     }
+
+    let input = consume(input, ")").map_err(|rest| reject(rest, Reason::MissingParen))?;
+
+    Ok((input, args))
+}
+
+/// A candidate instruction that is not well formed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rejection {
+    /// Byte offset of the instruction name.
+    pub offset: usize,
+    /// Byte offset where parsing stopped.
+    pub at: usize,
+    pub reason: Reason,
 }
 
-fn parse_mul_op(input: &str) -> Result<(&str, (i64, i64)), &str> {
-    let input = consume(input, "mul(")?;
-    let (input, a) = parse_number(input)?;
-    let input = consume(input, ",")?;
-    let (input, b) = parse_number(input)?;
-    let input = consume(input, ")")?;
+/// An instruction whose result does not fit in the accumulator.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overflow {
+    /// Byte offset of the instruction.
+    pub offset: usize,
+}
 
-    Ok((input, (a, b)))
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "accumulator overflow at byte {}", self.offset)
+    }
 }
 
-#[aoc(day3, part1)]
-pub fn solve_part1(mut input: &str) -> i64 {
-    let mut acc = 0;
+impl std::error::Error for Overflow {}
 
-    while let Ok(rest) = peek_until(input, "mul(") {
-        match parse_mul_op(rest) {
-            Ok((rest, (a, b))) => {
-                input = rest;
-                acc += a * b;
-            }
-            Err(rest) => input = rest,
+/// What instructions act on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Machine {
+    pub acc: i64,
+    pub enabled: bool,
+}
+
+impl Default for Machine {
+    fn default() -> Self {
+        Self {
+            acc: 0,
+            enabled: true,
         }
     }
+}
 
-    acc
+/// An instruction written `name(a,b,...)` with exactly `arity` operands.
+/// Its semantics return `None` if the result overflows.
+#[derive(Clone, Copy)]
+pub struct Definition {
+    pub name: &'static str,
+    pub arity: usize,
+    pub semantics: fn(&mut Machine, &[i64]) -> Option<()>,
 }
 
-#[aoc(day3, part2)]
-pub fn solve_part2(mut input: &str) -> i64 {
-    let mut acc = 0;
-    let mut enabled = true;
+impl fmt::Debug for Definition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Definition")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .finish()
+    }
+}
+
+/// A well formed instruction found in memory.
+#[derive(Debug, Clone)]
+pub struct Call {
+    pub definition: Definition,
+    pub args: Vec<i64>,
+    /// Byte offsets into the memory.
+    pub span: Range<usize>,
+}
+
+impl Call {
+    /// Leaves `machine` unchanged if the instruction overflows.
+    pub fn apply(&self, machine: &mut Machine) -> Result<(), Overflow> {
+        self.apply_as(&self.definition, machine)
+    }
+
+    /// [`apply`](Self::apply) with the semantics of `definition` instead.
+    fn apply_as(&self, definition: &Definition, machine: &mut Machine) -> Result<(), Overflow> {
+        let mut next = *machine;
+        (definition.semantics)(&mut next, &self.args).ok_or(Overflow {
+            offset: self.span.start,
+        })?;
+        *machine = next;
+
+        Ok(())
+    }
+}
+
+const MUL: Definition = Definition {
+    name: "mul",
+    arity: 2,
+    semantics: |m, args| {
+        if m.enabled {
+            m.acc = m.acc.checked_add(args[0].checked_mul(args[1])?)?;
+        }
+        Some(())
+    },
+};
+
+const DO: Definition = Definition {
+    name: "do",
+    arity: 0,
+    semantics: |m, _| {
+        m.enabled = true;
+        Some(())
+    },
+};
 
-    while let Ok((rest, idx)) = peek_until_first(input, &["mul(", "do()", "don't()"]) {
-        match idx {
-            0 => match parse_mul_op(rest) {
-                Ok((rest, (a, b))) => {
-                    input = rest;
+const DONT: Definition = Definition {
+    name: "don't",
+    arity: 0,
+    semantics: |m, _| {
+        m.enabled = false;
+        Some(())
+    },
+};
 
-                    if enabled {
-                        acc += a * b;
-                    }
+/// The instructions to look for in corrupted memory.
+#[derive(Debug, Clone, Default)]
+pub struct Registry {
+    definitions: Vec<Definition>,
+    patterns: Vec<String>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// `mul` on its own, as in part 1. Nothing disables it.
+    pub fn mul_only() -> Self {
+        let mut registry = Self::new();
+        registry.register(MUL);
+
+        registry
+    }
+
+    /// `mul`, `do` and `don't`, as in part 2.
+    pub fn standard() -> Self {
+        let mut registry = Self::mul_only();
+        registry.register(DO);
+        registry.register(DONT);
+
+        registry
+    }
+
+    /// Adds `definition`, replacing any other one with the same name.
+    pub fn register(&mut self, definition: Definition) {
+        match self
+            .definitions
+            .iter()
+            .position(|d| d.name == definition.name)
+        {
+            Some(idx) => self.definitions[idx] = definition,
+            None => {
+                self.patterns.push(format!("{}(", definition.name));
+                self.definitions.push(definition);
+            }
+        }
+    }
+
+    pub fn definitions(&self) -> &[Definition] {
+        &self.definitions
+    }
+
+    /// The definition registered as `name`.
+    pub fn definition(&self, name: &str) -> Option<&Definition> {
+        self.definitions.iter().find(|d| d.name == name)
+    }
+
+    /// Every well formed instruction in `memory`, in order.
+    pub fn scan(&self, memory: &str) -> Vec<Call> {
+        let mut calls = Vec::new();
+        self.scan_partial(memory, 0, true, &mut |call| calls.push(call));
+
+        calls
+    }
+
+    /// Every occurrence of the instruction `name` in `memory` that is not a
+    /// well formed instruction, with the reason it was rejected. Empty if
+    /// `name` is not registered.
+    pub fn audit(&self, memory: &str, name: &str) -> Vec<Rejection> {
+        let Some(definition) = self.definition(name) else {
+            return Vec::new();
+        };
+
+        let offset = |rest: &str| memory.len() - rest.len();
+
+        // Both the calls and the candidates are in order, so the only call
+        // that can hold a candidate is the first one not ending before it.
+        let mut calls = self.scan(memory).into_iter().peekable();
+
+        memory
+            .match_indices(name)
+            .map(|(start, _)| start)
+            .filter(|start| {
+                while calls.next_if(|call| call.span.end <= *start).is_some() {}
+                !calls.peek().is_some_and(|call| call.span.contains(start))
+            })
+            .filter_map(|start| {
+                let candidate = &memory[start + name.len()..];
+                let (rest, reason) = parse_call(candidate, "(", definition.arity).err()?;
+
+                Some(Rejection {
+                    offset: start,
+                    at: offset(rest),
+                    reason,
+                })
+            })
+            .collect()
+    }
+
+    /// Scans `input`, the part of a stream starting at byte `base`, and
+    /// returns how much of it is done with. Unless `at_end`, an instruction
+    /// cut off by the end of `input` is left for the next call, along with
+    /// any trailing text that could still become one.
+    fn scan_partial(
+        &self,
+        input: &str,
+        base: usize,
+        at_end: bool,
+        on_call: &mut impl FnMut(Call),
+    ) -> usize {
+        let patterns = self.patterns.iter().map(String::as_str).collect::<Vec<_>>();
+        let offset = |rest: &str| input.len() - rest.len();
+        let mut remaining = input;
+
+        while let Ok((rest, idx)) = peek_until_first(remaining, &patterns) {
+            let definition = self.definitions[idx];
+            let start = offset(rest);
+
+            remaining = match parse_call(rest, patterns[idx], definition.arity) {
+                Ok((rest, args)) => {
+                    on_call(Call {
+                        definition,
+                        args,
+                        span: base + start..base + offset(rest),
+                    });
+                    rest
                 }
-                Err(rest) => input = rest,
+                Err((_, Reason::Truncated)) if !at_end => return start,
+                Err((rest, _)) => rest,
+            };
+        }
+
+        if at_end {
+            return input.len();
+        }
+
+        let longest = patterns.iter().map(|p| p.len()).max().unwrap_or_default();
+        let keep = (1..longest.min(remaining.len() + 1))
+            .rev()
+            .map(|len| remaining.len() - len)
+            .filter(|start| remaining.is_char_boundary(*start))
+            .find(|start| patterns.iter().any(|p| p.starts_with(&remaining[*start..])))
+            .map_or(0, |start| remaining.len() - start);
+
+        input.len() - keep
+    }
+
+    /// Scans memory read from `reader` in chunks, passing each instruction
+    /// to `on_call` in order. Spans are offsets into the whole stream.
+    ///
+    /// Only an unfinished instruction at the end of a chunk is kept for the
+    /// next read, so memory use does not grow with the input. Bytes that
+    /// are not valid UTF-8 cannot be part of an instruction, and are
+    /// skipped like any other junk.
+    pub fn scan_reader(
+        &self,
+        mut reader: impl Read,
+        mut on_call: impl FnMut(Call),
+    ) -> io::Result<()> {
+        const CHUNK_SIZE: usize = 64 * 1024;
+
+        let mut chunk = vec![0; CHUNK_SIZE];
+        let mut pending = Vec::new();
+        let mut base = 0;
+
+        loop {
+            let read = match reader.read(&mut chunk) {
+                Ok(read) => read,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+            let at_end = read == 0;
+            pending.extend_from_slice(&chunk[..read]);
+
+            let text = valid_utf8_prefix(&mut pending, at_end);
+            let done = self.scan_partial(text, base, at_end, &mut on_call);
+
+            pending.drain(..done);
+            base += done;
+
+            if at_end {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Runs every instruction in `memory` on a fresh machine, stopping at
+    /// the first one that overflows.
+    pub fn run(&self, memory: &str) -> Result<Machine, Overflow> {
+        let mut machine = Machine::default();
+        for call in self.scan(memory) {
+            call.apply(&mut machine)?;
+        }
+
+        Ok(machine)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Mul(i64, i64),
+    Do,
+    Dont,
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Instruction::Mul(a, b) => write!(f, "mul({a},{b})"),
+            Instruction::Do => write!(f, "do()"),
+            Instruction::Dont => write!(f, "don't()"),
+        }
+    }
+}
+
+/// An instruction and where it is in the corrupted memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub instruction: Instruction,
+    /// Byte offsets into the memory.
+    pub span: Range<usize>,
+}
+
+/// The longest prefix of `bytes` that is valid UTF-8, after replacing
+/// invalid bytes with NUL to keep offsets unchanged. Unless `at_end`, a
+/// character cut off at the end is left out, as the rest of it is still to
+/// be read.
+fn valid_utf8_prefix(bytes: &mut [u8], at_end: bool) -> &str {
+    let mut valid = 0;
+
+    loop {
+        match std::str::from_utf8(&bytes[valid..]) {
+            Ok(_) => valid = bytes.len(),
+            Err(e) => {
+                valid += e.valid_up_to();
+
+                let invalid = match e.error_len() {
+                    Some(len) => len,
+                    None if at_end => bytes.len() - valid,
+                    None => break,
+                };
+
+                bytes[valid..valid + invalid].fill(0);
+                continue;
+            }
+        }
+
+        break;
+    }
+
+    std::str::from_utf8(&bytes[..valid]).unwrap()
+}
+
+/// Both parts' answers for memory read from `reader`, see
+/// [`Registry::scan_reader`]. An [`Overflow`] is reported as an
+/// [`io::ErrorKind::InvalidData`] error.
+///
+/// The memory is scanned once with the part 2 instructions. Each call that
+/// part 1 also has is run on a second machine, with part 1's definition.
+pub fn solve_reader(reader: impl Read) -> io::Result<(i64, i64)> {
+    let part1 = Registry::mul_only();
+    let mut machines = [Machine::default(); 2];
+    let mut overflow = None;
+
+    Registry::standard().scan_reader(reader, |call| {
+        if overflow.is_some() {
+            return;
+        }
+
+        let result = match part1.definition(call.definition.name) {
+            Some(definition) => call.apply_as(definition, &mut machines[0]),
+            None => Ok(()),
+        };
+        overflow = result.and_then(|()| call.apply(&mut machines[1])).err();
+    })?;
+
+    match overflow {
+        Some(overflow) => Err(io::Error::new(io::ErrorKind::InvalidData, overflow)),
+        None => Ok((machines[0].acc, machines[1].acc)),
+    }
+}
+
+/// Every `mul`, `do` and `don't` instruction in `memory`, in order.
+pub fn lex(memory: &str) -> Vec<Token> {
+    Registry::standard()
+        .scan(memory)
+        .into_iter()
+        .map(|call| Token {
+            instruction: match (call.definition.name, call.args.as_slice()) {
+                ("mul", [a, b]) => Instruction::Mul(*a, *b),
+                ("do", []) => Instruction::Do,
+                ("don't", []) => Instruction::Dont,
+                _ => unreachable!(),
             },
-            1 => {
-                enabled = true;
-                input = consume(rest, "do()").unwrap();
+            span: call.span,
+        })
+        .collect()
+}
+
+/// How [`render`] marks up memory.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Terminal escape codes.
+    Ansi,
+    /// A standalone HTML page.
+    Html,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ansi" => Ok(Format::Ansi),
+            "html" => Ok(Format::Html),
+            _ => Err(format!("unknown format {s:?}")),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Style {
+    Plain,
+    Disabled,
+    Mul,
+    SkippedMul,
+    Toggle,
+}
+
+const HTML_HEADER: &str = "\
+<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>Day 3 memory</title>
+<style>
+body { background: #111; color: #ccc; }
+.acc { color: #cc5; }
+.mul { color: #5f5; font-weight: bold; }
+.skipped { color: #666; text-decoration: line-through; }
+.toggle { color: #5cf; }
+.disabled { color: #666; }
+</style>
+</head>
+<body>
+<pre>
+";
+
+const HTML_FOOTER: &str = "\
+</pre>
+</body>
+</html>";
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+impl Format {
+    fn paint(self, text: &str, style: Style) -> String {
+        match self {
+            Format::Ansi => {
+                let code = match style {
+                    Style::Plain => return text.to_string(),
+                    Style::Disabled => "2",
+                    Style::Mul => "1;32",
+                    Style::SkippedMul => "2;9",
+                    Style::Toggle => "36",
+                };
+
+                format!("\x1b[{code}m{text}\x1b[0m")
+            }
+            Format::Html => {
+                let class = match style {
+                    Style::Plain => return escape_html(text),
+                    Style::Disabled => "disabled",
+                    Style::Mul => "mul",
+                    Style::SkippedMul => "skipped",
+                    Style::Toggle => "toggle",
+                };
+
+                format!("<span class=\"{class}\">{}</span>", escape_html(text))
             }
-            2 => {
-                enabled = false;
-                input = consume(rest, "don't()").unwrap();
+        }
+    }
+
+    fn margin(self, acc: &str) -> String {
+        match self {
+            Format::Ansi => format!("\x1b[33m{acc}\x1b[0m │ "),
+            Format::Html => format!("<span class=\"acc\">{acc}</span> │ "),
+        }
+    }
+}
+
+/// `memory` line by line, with the instructions part 2 runs highlighted,
+/// the ones it skips struck out and the text between a `don't()` and the
+/// next `do()` dimmed. The margin holds the accumulator after each line.
+///
+/// Calls are run through [`Registry::standard`] as in [`solve_part2`]. Ones
+/// without operands are shown as toggles.
+pub fn render(memory: &str, format: Format) -> Result<String, Overflow> {
+    let mut machine = Machine::default();
+    let mut segments = Vec::new();
+    let mut totals = Vec::new();
+    let mut pos = 0;
+
+    let text = |machine: &Machine| {
+        if machine.enabled {
+            Style::Plain
+        } else {
+            Style::Disabled
+        }
+    };
+
+    for call in Registry::standard().scan(memory) {
+        let style = if call.definition.arity == 0 {
+            Style::Toggle
+        } else if machine.enabled {
+            Style::Mul
+        } else {
+            Style::SkippedMul
+        };
+
+        segments.push((pos..call.span.start, text(&machine)));
+        call.apply(&mut machine)?;
+        segments.push((call.span.clone(), style));
+        totals.push((call.span.end, machine.acc));
+        pos = call.span.end;
+    }
+    segments.push((pos..memory.len(), text(&machine)));
+    segments.retain(|(range, _)| !range.is_empty());
+
+    let mut lines = Vec::new();
+    let mut totals = totals.into_iter().peekable();
+    let mut acc = 0;
+    let mut segment = 0;
+    let mut start = 0;
+
+    for line in memory.split_inclusive('\n') {
+        let end = start + line.trim_end_matches(['\n', '\r']).len();
+
+        while let Some((_, total)) = totals.next_if(|(at, _)| *at <= end) {
+            acc = total;
+        }
+        while segments
+            .get(segment)
+            .is_some_and(|(range, _)| range.end <= start)
+        {
+            segment += 1;
+        }
+
+        let body = segments[segment..]
+            .iter()
+            .take_while(|(range, _)| range.start < end)
+            .map(|(range, style)| {
+                format.paint(&memory[range.start.max(start)..range.end.min(end)], *style)
+            })
+            .collect::<String>();
+
+        lines.push((acc.to_string(), body));
+        start += line.len();
+    }
+
+    let width = lines.iter().map(|(acc, _)| acc.len()).max().unwrap_or(0);
+    let lines = lines
+        .iter()
+        .map(|(acc, body)| format!("{}{body}", format.margin(&format!("{acc:>width$}"))))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Ok(match format {
+        Format::Ansi => lines,
+        Format::Html => format!("{HTML_HEADER}{lines}\n{HTML_FOOTER}"),
+    })
+}
+
+#[aoc(day3, part1)]
+pub fn solve_part1(input: &str) -> Result<i64, Overflow> {
+    Ok(Registry::mul_only().run(input)?.acc)
+}
+
+#[aoc(day3, part2)]
+pub fn solve_part2(input: &str) -> Result<i64, Overflow> {
+    Ok(Registry::standard().run(input)?.acc)
+}
+
+pub struct Day3;
+
+impl Solution for Day3 {
+    const DAY: u8 = 3;
+
+    type Input = String;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        Ok(input_gen(input))
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer, SolveError> {
+        solve_part1(input)
+            .map(Answer::from)
+            .map_err(|e| SolveError::new(Self::DAY, e.to_string()))
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer, SolveError> {
+        solve_part2(input)
+            .map(Answer::from)
+            .map_err(|e| SolveError::new(Self::DAY, e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(
+            solve_part1("xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))"),
+            Ok(161)
+        );
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(
+            solve_part2(
+                "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))"
+            ),
+            Ok(48)
+        );
+    }
+
+    #[test]
+    fn lex_example() {
+        let memory = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+        let tokens = lex(memory);
+
+        let stream = tokens
+            .iter()
+            .map(|t| t.instruction.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            stream,
+            [
+                "mul(2,4)",
+                "don't()",
+                "mul(5,5)",
+                "mul(11,8)",
+                "do()",
+                "mul(8,5)"
+            ]
+        );
+
+        for token in &tokens {
+            assert_eq!(memory[token.span.clone()], token.instruction.to_string());
+        }
+        assert_eq!(tokens[0].span, 1..9);
+    }
+
+    #[test]
+    fn registry_runs_part2() {
+        let memory = "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))";
+
+        assert_eq!(Registry::standard().run(memory).unwrap().acc, 48);
+    }
+
+    #[test]
+    fn registry_extensions() {
+        let mut registry = Registry::standard();
+        registry.register(Definition {
+            name: "add",
+            arity: 2,
+            semantics: |m, args| {
+                m.acc = m.acc.checked_add(args[0] + args[1])?;
+                Some(())
+            },
+        });
+        registry.register(Definition {
+            name: "sub",
+            arity: 1,
+            semantics: |m, args| {
+                m.acc = m.acc.checked_sub(args[0])?;
+                Some(())
+            },
+        });
+        registry.register(Definition {
+            name: "toggle",
+            arity: 0,
+            semantics: |m, _| {
+                m.enabled = !m.enabled;
+                Some(())
+            },
+        });
+
+        let memory = "add(1,2)mul(2,3)sub(4)toggle()mul(5,5)add(1)sub(2,3)toggle()mul(1,1)";
+        let machine = registry.run(memory).unwrap();
+
+        assert_eq!(registry.scan(memory).len(), 7);
+        assert_eq!(machine.acc, 3 + 6 - 4 + 1);
+        assert!(machine.enabled);
+    }
+
+    #[test]
+    fn registry_replaces_definitions() {
+        let mut registry = Registry::standard();
+        registry.register(Definition {
+            name: "mul",
+            arity: 3,
+            semantics: |m, args| {
+                m.acc = m.acc.checked_add(args.iter().product())?;
+                Some(())
+            },
+        });
+
+        assert_eq!(registry.definitions().len(), 3);
+        assert_eq!(registry.run("mul(2,3)mul(2,3,4)").unwrap().acc, 24);
+    }
+
+    #[test]
+    fn registry_overflow() {
+        let mut registry = Registry::standard();
+        registry.register(Definition {
+            name: "pow",
+            arity: 1,
+            semantics: |m, args| {
+                m.acc = args[0].checked_pow(m.acc.max(1) as u32)?;
+                Some(())
+            },
+        });
+
+        assert_eq!(registry.run("pow(10)pow(10)").unwrap().acc, 10_000_000_000);
+        assert_eq!(
+            registry.run("pow(10)mul(1,8)pow(10)xpow(10)"),
+            Err(Overflow { offset: 23 })
+        );
+    }
+
+    #[test]
+    fn checked_mul() {
+        let calls = Registry::standard().scan("mul(2,3)don't()mul(1,1)");
+
+        let mut machine = Machine {
+            acc: i64::MAX - 6,
+            enabled: true,
+        };
+        for call in &calls {
+            assert_eq!(call.apply(&mut machine), Ok(()));
+        }
+        assert_eq!(machine.acc, i64::MAX);
+
+        machine.enabled = true;
+        assert_eq!(calls[2].apply(&mut machine), Err(Overflow { offset: 15 }));
+        assert_eq!(machine.acc, i64::MAX);
+    }
+
+    #[test]
+    fn operand_digits() {
+        assert_eq!(solve_part1("mul(999,999)mul(0,007)"), Ok(998001));
+        assert_eq!(
+            solve_part1("mul(1234,5)mul(5,1234)mul(-1,2)mul(+1,2)"),
+            Ok(0)
+        );
+        assert_eq!(solve_part1("mul(٣,4)mul(3,４)mul(²,2)"), Ok(0));
+    }
+
+    #[test]
+    fn audit_near_misses() {
+        let memory = "mul(4*mul ( 2 , 4 )mul(1234,5)mul(2,3]mul(,2)do_not_mul(5,5)mul(8,";
+        let rejections = Registry::standard()
+            .audit(memory, "mul")
+            .iter()
+            .map(|r| (&memory[r.offset..r.at], r.reason))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            rejections,
+            [
+                ("mul(4", Reason::BadSeparator),
+                ("mul", Reason::MissingParen),
+                ("mul(123", Reason::TooManyDigits),
+                ("mul(2,3", Reason::MissingParen),
+                ("mul(", Reason::NonDigit),
+                ("mul(8,", Reason::Truncated),
+            ]
+        );
+
+        assert_eq!(Registry::standard().audit(memory, "add"), []);
+    }
+
+    /// Hands out at most `step` bytes per read.
+    struct Trickle<'a> {
+        data: &'a [u8],
+        step: usize,
+    }
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let len = self.step.min(buf.len()).min(self.data.len());
+            buf[..len].copy_from_slice(&self.data[..len]);
+            self.data = &self.data[len..];
+
+            Ok(len)
+        }
+    }
+
+    fn stream_spans(data: &[u8], step: usize) -> Vec<(usize, usize)> {
+        let mut spans = Vec::new();
+        Registry::standard()
+            .scan_reader(Trickle { data, step }, |call| {
+                spans.push((call.span.start, call.span.end))
+            })
+            .unwrap();
+
+        spans
+    }
+
+    #[test]
+    fn streaming_matches_whole_input() {
+        let params = crate::synth::Params {
+            size: 3000,
+            density: 0.4,
+        };
+
+        for seed in 0..4 {
+            let memory = crate::synth::day3(&mut crate::rng::Rng::new(seed), &params)
+                .replace('#', "é")
+                .replace('%', "€");
+            let spans = lex(&memory)
+                .into_iter()
+                .map(|t| (t.span.start, t.span.end))
+                .collect::<Vec<_>>();
+
+            for step in [1, 2, 3, 7, 64, 4096] {
+                assert_eq!(stream_spans(memory.as_bytes(), step), spans, "step {step}");
             }
-            _ => unreachable!(),
+
+            let totals = solve_reader(Trickle {
+                data: memory.as_bytes(),
+                step: 5,
+            })
+            .unwrap();
+            assert_eq!(
+                totals,
+                (solve_part1(&memory).unwrap(), solve_part2(&memory).unwrap())
+            );
         }
     }
 
-    acc
+    #[test]
+    fn streaming_invalid_utf8() {
+        let data = b"mul(2,\xff3)mul(1,\xe2\x82mul(2,3)\xe2\x82";
+
+        assert_eq!(stream_spans(data, 1), [(17, 25)]);
+        assert_eq!(stream_spans(data, 100), [(17, 25)]);
+    }
+
+    #[test]
+    fn streaming_unfinished_instruction() {
+        assert_eq!(stream_spans(b"mul(2,3)mul(4,", 1), [(0, 8)]);
+        assert_eq!(stream_spans(b"xxdon", 2), []);
+        assert_eq!(solve_reader(&b""[..]).unwrap(), (0, 0));
+    }
+
+    #[test]
+    fn render_ansi() {
+        let memory =
+            "xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+\nmul(32,64](mul(11,8)undo()?mul(8,5))\n";
+        let rendered = render(memory, Format::Ansi).unwrap();
+        let lines = rendered.lines().collect::<Vec<_>>();
+
+        assert_eq!(
+            lines[0],
+            "\x1b[33m 8\x1b[0m │ x\x1b[1;32mmul(2,4)\x1b[0m&mul[3,7]!^\x1b[36mdon't()\x1b[0m\
+             \x1b[2m_\x1b[0m\x1b[2;9mmul(5,5)\x1b[0m\x1b[2m+\x1b[0m"
+        );
+        assert!(lines[1].starts_with("\x1b[33m48\x1b[0m │ \x1b[2mmul(32,64](\x1b[0m"));
+        assert!(lines[1].ends_with("\x1b[1;32mmul(8,5)\x1b[0m)"));
+        assert_eq!(lines.len(), 2);
+    }
+
+    #[test]
+    fn render_html() {
+        let memory = "<mul(2,4)>don't()&";
+        let rendered = render(memory, Format::Html).unwrap();
+
+        assert!(rendered.starts_with("<!DOCTYPE html>"));
+        assert!(rendered.contains(
+            "<span class=\"acc\">8</span> │ &lt;<span class=\"mul\">mul(2,4)</span>&gt;\
+             <span class=\"toggle\">don't()</span><span class=\"disabled\">&amp;</span>\n"
+        ));
+        assert!(rendered.ends_with("</html>"));
+
+        assert_eq!(render("", Format::Ansi).unwrap(), "");
+    }
+
+    #[test]
+    fn empty_input() {
+        assert_eq!(solve_part1(&input_gen("")), Ok(0));
+        assert_eq!(solve_part2(&input_gen("")), Ok(0));
+    }
+
+    #[test]
+    fn truncated_instruction() {
+        assert_eq!(solve_part1("mul(2,4)mul(3,"), Ok(8));
+        assert_eq!(solve_part2("don't()mul(2,4)do("), Ok(0));
+    }
 }
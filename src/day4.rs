@@ -1,87 +1,363 @@
+use std::collections::VecDeque;
+
 use aoc_runner_derive::{aoc, aoc_generator};
 
+use crate::{
+    geometry::{Direction8, Position},
+    grid::Grid,
+    parse::ParseError,
+    solution::{Answer, Solution, SolveError},
+};
+
 #[aoc_generator(day4)]
-pub fn input_gen(input: &str) -> Vec<Vec<char>> {
+pub fn input_gen(input: &str) -> Result<Grid<char>, ParseError> {
     input
-        .lines()
-        .map(|l| l.chars().collect::<Vec<_>>())
-        .collect()
+        .parse()
+        .map_err(|e| ParseError::from_grid(4, input, e))
+}
+
+/// Where a word starts in the grid, and which way it reads from there.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Occurrence {
+    pub start: Position,
+    pub direction: Direction8,
+}
+
+/// Every occurrence of one word.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WordMatches {
+    pub word: String,
+    pub occurrences: Vec<Occurrence>,
+}
+
+impl WordMatches {
+    pub fn count(&self) -> usize {
+        self.occurrences.len()
+    }
 }
 
-const DIRECTIONS: [(i64, i64); 8] = [
-    (0, 1),
-    (0, -1),
-    (1, 0),
-    (-1, 0),
-    (1, 1),
-    (1, -1),
-    (-1, 1),
-    (-1, -1),
+/// One direction along each line of the grid. Words are also looked for
+/// backwards, which covers the opposite directions.
+const ORIENTATIONS: [Direction8; 4] = [
+    Direction8::Right,
+    Direction8::DownRight,
+    Direction8::Down,
+    Direction8::DownLeft,
 ];
 
-#[aoc(day4, part1)]
-pub fn solve_part1(input: &[Vec<char>]) -> usize {
-    const WORD: &str = "XMAS";
+/// An Aho-Corasick automaton over a list of words and their reverses,
+/// finding all of them in a single pass over each line of a grid.
+#[derive(Debug, Clone)]
+pub struct WordSearch {
+    words: Vec<String>,
+    /// Every letter of the words, sorted. A letter's index is its symbol.
+    alphabet: Vec<char>,
+    /// `alphabet.len()` transitions per state, with the failure links
+    /// already followed.
+    next: Vec<usize>,
+    /// The words ending in each state, and whether they were read
+    /// backwards.
+    matches: Vec<Vec<(usize, bool)>>,
+}
 
-    input
-        .iter()
-        .enumerate()
-        .map(|(y, v)| v.iter().enumerate().map(move |(x, c)| ((x, y), *c)))
-        .flatten()
-        .filter(|(_, c)| *c == WORD.chars().next().unwrap())
-        .map(|(p, _)| DIRECTIONS.iter().map(move |d| (p, *d)))
-        .flatten()
-        .filter(|((x, y), (dx, dy))| {
-            WORD.chars().skip(1).eq((1..WORD.len()).scan((), |_, i| {
-                let x1 = *x as i64 + i as i64 * dx;
-                let y1 = *y as i64 + i as i64 * dy;
-
-                if x1 >= 0
-                    && y1 >= 0
-                    && (y1 as usize) < input.len()
-                    && (x1 as usize) < input[y1 as usize].len()
-                {
-                    Some(input[y1 as usize][x1 as usize])
+impl WordSearch {
+    pub fn new<S: AsRef<str>>(words: &[S]) -> Self {
+        let words = words
+            .iter()
+            .map(|w| w.as_ref().to_string())
+            .collect::<Vec<_>>();
+
+        let mut alphabet = words.iter().flat_map(|w| w.chars()).collect::<Vec<_>>();
+        alphabet.sort_unstable();
+        alphabet.dedup();
+
+        let stride = alphabet.len();
+        let symbol = |c: char| alphabet.binary_search(&c).unwrap();
+
+        // The trie, with `usize::MAX` for missing children.
+        let mut next = vec![usize::MAX; stride];
+        let mut matches = vec![Vec::new()];
+
+        for (idx, word) in words.iter().enumerate() {
+            if word.is_empty() {
+                continue;
+            }
+
+            for backwards in [false, true] {
+                let letters = if backwards {
+                    word.chars().rev().collect::<Vec<_>>()
+                } else {
+                    word.chars().collect()
+                };
+
+                let mut state = 0;
+                for c in letters {
+                    let edge = state * stride + symbol(c);
+
+                    if next[edge] == usize::MAX {
+                        next[edge] = matches.len();
+                        next.extend(std::iter::repeat_n(usize::MAX, stride));
+                        matches.push(Vec::new());
+                    }
+                    state = next[edge];
+                }
+                matches[state].push((idx, backwards));
+            }
+        }
+
+        // Breadth first, so the state a failure link points to is complete
+        // before it is used.
+        let mut fail = vec![0; matches.len()];
+        let mut queue = VecDeque::from([0]);
+
+        while let Some(state) = queue.pop_front() {
+            for symbol in 0..stride {
+                let edge = state * stride + symbol;
+                let fallback = if state == 0 {
+                    0
+                } else {
+                    next[fail[state] * stride + symbol]
+                };
+
+                if next[edge] == usize::MAX {
+                    next[edge] = fallback;
                 } else {
-                    None
+                    let child = next[edge];
+                    let inherited = matches[fallback].clone();
+
+                    fail[child] = fallback;
+                    matches[child].extend(inherited);
+                    queue.push_back(child);
+                }
+            }
+        }
+
+        Self {
+            words,
+            alphabet,
+            next,
+            matches,
+        }
+    }
+
+    /// Every occurrence of each word in all eight directions, in the order
+    /// the words were given. A palindrome is found once for each way it
+    /// reads, and an empty word is never found.
+    pub fn find(&self, grid: &Grid<char>) -> Vec<WordMatches> {
+        let stride = self.alphabet.len();
+        let lengths = self
+            .words
+            .iter()
+            .map(|w| w.chars().count() as i64)
+            .collect::<Vec<_>>();
+        let mut found = self
+            .words
+            .iter()
+            .map(|word| WordMatches {
+                word: word.clone(),
+                occurrences: Vec::new(),
+            })
+            .collect::<Vec<_>>();
+
+        let symbols = grid.map(|c| self.alphabet.binary_search(c).ok());
+
+        for direction in ORIENTATIONS {
+            let step = direction.offset();
+
+            for ray in symbols.rays(step) {
+                let mut state = 0;
+
+                for (p, symbol) in ray {
+                    state = match symbol {
+                        Some(symbol) => self.next[state * stride + symbol],
+                        None => 0,
+                    };
+
+                    for &(word, backwards) in &self.matches[state] {
+                        found[word].occurrences.push(if backwards {
+                            Occurrence {
+                                start: p,
+                                direction: direction.reverse(),
+                            }
+                        } else {
+                            Occurrence {
+                                start: p - step * (lengths[word] - 1),
+                                direction,
+                            }
+                        });
+                    }
                 }
-            }))
+            }
+        }
+
+        found
+    }
+}
+
+/// Checks every cell and direction for `word` separately.
+pub(crate) fn find_word_naive(grid: &Grid<char>, word: &str) -> Vec<Occurrence> {
+    if word.is_empty() {
+        return Vec::new();
+    }
+
+    grid.positions()
+        .flat_map(|start| Direction8::ALL.map(move |direction| Occurrence { start, direction }))
+        .filter(|o| {
+            grid.ray(o.start, o.direction.offset())
+                .map(|(_, c)| *c)
+                .take(word.chars().count())
+                .eq(word.chars())
         })
-        .count()
+        .collect()
+}
+
+#[aoc(day4, part1)]
+pub fn solve_part1(input: &Grid<char>) -> usize {
+    WordSearch::new(&["XMAS"]).find(input)[0].count()
 }
 
 #[aoc(day4, part2)]
-pub fn solve_part2(input: &[Vec<char>]) -> usize {
+pub fn solve_part2(input: &Grid<char>) -> usize {
     input
         .iter()
-        .enumerate()
-        .map(|(y, v)| v.iter().enumerate().map(move |(x, c)| ((x, y), *c)))
-        .flatten()
-        .filter(|(_, c)| *c == 'A')
+        .filter(|(_, c)| **c == 'A')
         .map(|(p, _)| p)
-        .filter(|(x, y)| {
-            *x as i64 - 1 >= 0
-                && *y as i64 - 1 >= 0
-                && *y + 1 < input.len()
-                && *x + 1 < input[*y].len()
+        .filter_map(|p| {
+            let corner = |d: Direction8| input.get(p + d.offset()).copied();
+
+            Some([
+                corner(Direction8::UpLeft)?,
+                corner(Direction8::UpRight)?,
+                corner(Direction8::DownLeft)?,
+                corner(Direction8::DownRight)?,
+            ])
         })
-        .filter(|(x, y)| {
-            input[*y - 1][*x - 1] == 'M'
-                && input[*y - 1][*x + 1] == 'S'
-                && input[*y + 1][*x - 1] == 'M'
-                && input[*y + 1][*x + 1] == 'S'
-                || input[*y - 1][*x - 1] == 'S'
-                    && input[*y - 1][*x + 1] == 'S'
-                    && input[*y + 1][*x - 1] == 'M'
-                    && input[*y + 1][*x + 1] == 'M'
-                || input[*y - 1][*x - 1] == 'S'
-                    && input[*y - 1][*x + 1] == 'M'
-                    && input[*y + 1][*x - 1] == 'S'
-                    && input[*y + 1][*x + 1] == 'M'
-                || input[*y - 1][*x - 1] == 'M'
-                    && input[*y - 1][*x + 1] == 'M'
-                    && input[*y + 1][*x - 1] == 'S'
-                    && input[*y + 1][*x + 1] == 'S'
+        .filter(|corners| {
+            matches!(
+                corners,
+                ['M', 'S', 'M', 'S']
+                    | ['S', 'S', 'M', 'M']
+                    | ['S', 'M', 'S', 'M']
+                    | ['M', 'M', 'S', 'S']
+            )
         })
         .count()
 }
+
+pub struct Day4;
+
+impl Solution for Day4 {
+    const DAY: u8 = 4;
+
+    type Input = Grid<char>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_gen(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(solve_part1(input).into())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(solve_part2(input).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+MMMSXXMASM
+MSAMXMSMSA
+AMXSXMAAMM
+MSAMASMSMX
+XMASAMXAMM
+XXAMMXXAMA
+SMSMSASXSS
+SAXAMASAAA
+MAMMMXMMMM
+MXMXAXMASX
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(solve_part1(&input_gen(EXAMPLE).unwrap()), 18);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(solve_part2(&input_gen(EXAMPLE).unwrap()), 9);
+    }
+
+    #[test]
+    fn word_search_example() {
+        let grid = input_gen(EXAMPLE).unwrap();
+        let found = WordSearch::new(&["XMAS", "SAMX", "MAS", "XMAS", ""]).find(&grid);
+
+        let counts = found.iter().map(WordMatches::count).collect::<Vec<_>>();
+        assert_eq!(counts, [18, 18, find_word_naive(&grid, "MAS").len(), 18, 0]);
+        assert_eq!(found[3], found[0]);
+
+        let mut occurrences = found[0].occurrences.clone();
+        occurrences.sort();
+        assert_eq!(
+            occurrences[..2],
+            [
+                Occurrence {
+                    start: Position(0, 4),
+                    direction: Direction8::Right,
+                },
+                Occurrence {
+                    start: Position(0, 5),
+                    direction: Direction8::UpRight,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn word_search_palindromes() {
+        let grid = input_gen("ABA\nBAB\n").unwrap();
+        let found = WordSearch::new(&["ABA", "A", "BAB", "AB"]).find(&grid);
+
+        let mut aba = found[0].occurrences.clone();
+        aba.sort();
+        assert_eq!(
+            aba,
+            [
+                Occurrence {
+                    start: Position(0, 0),
+                    direction: Direction8::Right,
+                },
+                Occurrence {
+                    start: Position(2, 0),
+                    direction: Direction8::Left,
+                },
+            ]
+        );
+        assert_eq!(found[1].count(), 3 * 8);
+        assert_eq!(found[2].count(), 2);
+        assert_eq!(found[3].count(), find_word_naive(&grid, "AB").len());
+    }
+
+    #[test]
+    fn input_gen_empty() {
+        assert!(input_gen("").is_err());
+    }
+
+    #[test]
+    fn input_gen_single_line() {
+        let input = input_gen("XMASAMX").unwrap();
+
+        assert_eq!(solve_part1(&input), 2);
+        assert_eq!(solve_part2(&input), 0);
+    }
+
+    #[test]
+    fn input_gen_rejects_ragged_lines() {
+        let err = input_gen("XMAS\nXMA").unwrap_err();
+        assert_eq!(err.line, 2);
+    }
+}
@@ -2,6 +2,11 @@ use std::collections::BTreeMap;
 
 use aoc_runner_derive::{aoc, aoc_generator};
 
+use crate::{
+    parse::{lines, ParseError},
+    solution::{Answer, Solution, SolveError},
+};
+
 type Ruleset = BTreeMap<i64, Vec<i64>>;
 
 pub struct Input {
@@ -10,32 +15,33 @@ pub struct Input {
 }
 
 #[aoc_generator(day5)]
-pub fn input_gen(input: &str) -> Input {
-    let mut parts = input.split("\n\n");
-    let rules_str = parts.next().unwrap();
-    let updates_str = parts.next().unwrap();
+pub fn input_gen(input: &str) -> Result<Input, ParseError> {
+    let mut lines = lines(5, input);
 
     let mut rules = Ruleset::new();
-    rules_str
-        .lines()
-        .map(|l| {
-            let mut parts = l.split('|').map(|d| d.parse::<i64>().unwrap());
-            (parts.next().unwrap(), parts.next().unwrap())
-        })
-        .for_each(|(a, b)| {
-            rules.entry(a).or_insert_with(Vec::new).push(b);
-        });
+    for l in lines.by_ref().take_while(|l| !l.text().trim().is_empty()) {
+        let (a, b) = l.split_once('|')?;
+        rules.entry(l.parse(a)?).or_default().push(l.parse(b)?);
+    }
 
-    let updates = updates_str
-        .lines()
+    let updates = lines
         .map(|l| {
-            l.split(',')
-                .map(|d| d.parse::<i64>().unwrap())
-                .collect::<Vec<_>>()
+            l.text()
+                .split(',')
+                .map(|d| l.parse(d))
+                .collect::<Result<Vec<_>, _>>()
         })
-        .collect();
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if updates.is_empty() {
+        return Err(ParseError::at_end(
+            5,
+            input,
+            "expected a blank line followed by page updates",
+        ));
+    }
 
-    Input { rules, updates }
+    Ok(Input { rules, updates })
 }
 
 fn check_rules(rules: &Ruleset, update: &[i64]) -> Option<(usize, usize)> {
@@ -48,7 +54,7 @@ fn check_rules(rules: &Ruleset, update: &[i64]) -> Option<(usize, usize)> {
                 .find(|(_, v)| *v == target)
                 .map(|(idx, _)| (idx, pages))
         })
-        .map(|(target_idx, pages)| {
+        .flat_map(|(target_idx, pages)| {
             pages.iter().filter_map(move |n| {
                 update[..target_idx]
                     .iter()
@@ -57,7 +63,6 @@ fn check_rules(rules: &Ruleset, update: &[i64]) -> Option<(usize, usize)> {
                     .map(|(idx, _)| (target_idx, idx))
             })
         })
-        .flatten()
         .next()
 }
 
@@ -75,16 +80,115 @@ pub fn solve_part1(input: &Input) -> i64 {
 pub fn solve_part2(input: &Input) -> i64 {
     input
         .updates
-        .clone()
-        .iter_mut()
-        .filter(|u| !check_rules(&input.rules, u).is_none())
+        .iter()
+        .filter(|u| check_rules(&input.rules, u).is_some())
         .map(|u| {
-            while let Some((a, b)) = check_rules(&input.rules, u) {
+            let mut u = u.clone();
+
+            while let Some((a, b)) = check_rules(&input.rules, &u) {
                 u.swap(a, b);
             }
 
-            u
+            u[u.len() / 2]
         })
-        .map(|u| u[u.len() / 2])
         .sum()
 }
+
+pub struct Day5;
+
+impl Solution for Day5 {
+    const DAY: u8 = 5;
+
+    type Input = Input;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_gen(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(solve_part1(input).into())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(solve_part2(input).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+47|53
+97|13
+97|61
+97|47
+75|29
+61|13
+75|53
+29|13
+97|29
+53|29
+61|53
+97|53
+61|29
+47|13
+75|47
+97|75
+47|61
+75|61
+47|29
+75|13
+53|13
+
+75,47,61,53,29
+97,61,53,29,13
+75,29,13
+75,97,47,61,53
+61,13,29
+97,13,75,29,47
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(solve_part1(&input_gen(EXAMPLE).unwrap()), 143);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(solve_part2(&input_gen(EXAMPLE).unwrap()), 123);
+    }
+
+    #[test]
+    fn input_gen_crlf() {
+        let input = input_gen(&EXAMPLE.replace('\n', "\r\n")).unwrap();
+
+        assert_eq!(solve_part1(&input), 143);
+    }
+
+    #[test]
+    fn input_gen_empty() {
+        assert!(input_gen("").is_err());
+    }
+
+    #[test]
+    fn input_gen_single_update_without_rules() {
+        let input = input_gen("\n1,2,3").unwrap();
+
+        assert_eq!(solve_part1(&input), 2);
+        assert_eq!(solve_part2(&input), 0);
+    }
+
+    #[test]
+    fn input_gen_max_values() {
+        let input = input_gen(&format!("1|{0}\n\n{0},1,{0}", i64::MAX)).unwrap();
+
+        assert_eq!(solve_part2(&input), i64::MAX);
+    }
+
+    #[test]
+    fn input_gen_rejects_bad_rule() {
+        let err = input_gen("47-53\n\n47,53").err().unwrap();
+        assert_eq!((err.line, err.column), (1, 6));
+    }
+}
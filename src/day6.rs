@@ -1,43 +1,13 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashMap;
 
 use aoc_runner_derive::{aoc, aoc_generator};
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-struct Position(i64, i64);
-
-impl Position {
-    fn step(&self, dir: Direction) -> Position {
-        match dir {
-            Direction::Up => Position(self.0, self.1 - 1),
-            Direction::Right => Position(self.0 + 1, self.1),
-            Direction::Down => Position(self.0, self.1 + 1),
-            Direction::Left => Position(self.0 - 1, self.1),
-        }
-    }
-
-    fn is_within(&self, bounds: (i64, i64)) -> bool {
-        self.0 >= 0 && self.1 >= 0 && self.0 < bounds.0 && self.1 < bounds.1
-    }
-}
-
-#[derive(Clone, Copy, PartialEq)]
-enum Direction {
-    Up,
-    Right,
-    Down,
-    Left,
-}
-
-impl Direction {
-    fn turn_right(self) -> Direction {
-        match self {
-            Direction::Up => Direction::Right,
-            Direction::Right => Direction::Down,
-            Direction::Down => Direction::Left,
-            Direction::Left => Direction::Up,
-        }
-    }
-}
+use crate::{
+    geometry::{Direction, Position},
+    grid::Grid,
+    parse::ParseError,
+    solution::{Answer, Solution, SolveError},
+};
 
 #[derive(Clone)]
 struct Guard {
@@ -47,23 +17,27 @@ struct Guard {
 
 #[derive(Clone)]
 pub struct World {
-    map: HashSet<Position>,
-    bounds: (i64, i64),
+    map: Grid<bool>,
     visited: HashMap<Position, Vec<Direction>>,
     guard: Guard,
+    /// Turns since the guard last moved. After four, it is boxed in.
+    turns: u8,
     looping: bool,
 }
 
 impl World {
     fn step(&mut self) -> bool {
-        let new_pos = self.guard.pos.step(self.guard.dir);
-        let is_in_map = new_pos.is_within(self.bounds);
+        let new_pos = self.guard.pos + self.guard.dir.offset();
+        let obstacle = self.map.get(new_pos).copied();
 
-        if is_in_map {
-            if self.map.contains(&new_pos) {
+        if let Some(obstacle) = obstacle {
+            if obstacle {
                 self.guard.dir = self.guard.dir.turn_right();
+                self.turns += 1;
+                self.looping = self.turns == 4;
             } else {
                 self.guard.pos = new_pos;
+                self.turns = 0;
 
                 self.looping = self
                     .visited
@@ -73,12 +47,12 @@ impl World {
 
                 self.visited
                     .entry(self.guard.pos)
-                    .or_insert_with(Vec::new)
+                    .or_default()
                     .push(self.guard.dir);
             }
         }
 
-        is_in_map && !self.looping
+        obstacle.is_some() && !self.looping
     }
 
     fn simulate(&mut self) {
@@ -99,44 +73,31 @@ impl World {
 }
 
 #[aoc_generator(day6)]
-pub fn input_gen(input: &str) -> World {
-    let mut it = input
-        .lines()
-        .enumerate()
-        .map(|(y, l)| {
-            l.chars()
-                .enumerate()
-                .map(move |(x, c)| (Position(x as i64, y as i64), c))
-        })
-        .flatten();
-
-    let map = it
-        .clone()
-        .filter(|(_, c)| *c == '#')
-        .map(|(p, _)| p)
-        .collect();
+pub fn input_gen(input: &str) -> Result<World, ParseError> {
+    let cells = Grid::parse_with(input, |c| matches!(c, '.' | '#' | '^').then_some(c))
+        .map_err(|e| ParseError::from_grid(6, input, e))?;
 
-    let guard = it
-        .find(|(_, c)| *c == '^')
+    let guard = cells
+        .iter()
+        .find(|(_, c)| **c == '^')
         .map(|(pos, _)| Guard {
             pos,
             dir: Direction::Up,
         })
-        .unwrap();
+        .ok_or_else(|| ParseError::at_end(6, input, "expected a guard marked with '^'"))?;
 
-    let height = input.lines().count() as i64;
-    let width = input.lines().next().unwrap().chars().count() as i64;
+    let map = cells.map(|c| *c == '#');
 
     let mut visited = HashMap::new();
     visited.insert(guard.pos, vec![guard.dir]);
 
-    World {
+    Ok(World {
         map,
-        bounds: (width, height),
         visited,
         guard,
+        turns: 0,
         looping: false,
-    }
+    })
 }
 
 #[aoc(day6, part1)]
@@ -164,9 +125,93 @@ pub fn solve_part2(input: &World) -> usize {
         .filter(|p| {
             let mut world = input.clone();
 
-            world.map.insert(*p);
+            world.map[*p] = true;
             world.simulate();
             world.is_looping()
         })
         .count()
 }
+
+pub struct Day6;
+
+impl Solution for Day6 {
+    const DAY: u8 = 6;
+
+    type Input = World;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_gen(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(solve_part1(input).into())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(solve_part2(input).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(solve_part1(&input_gen(EXAMPLE).unwrap()), 41);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(solve_part2(&input_gen(EXAMPLE).unwrap()), 6);
+    }
+
+    #[test]
+    fn input_gen_empty() {
+        assert!(input_gen("").is_err());
+    }
+
+    #[test]
+    fn input_gen_single_line() {
+        let input = input_gen("#..^..").unwrap();
+
+        assert_eq!(solve_part1(&input), 1);
+        assert_eq!(solve_part2(&input), 0);
+    }
+
+    #[test]
+    fn boxed_in_guard() {
+        let input = input_gen("###\n#^#\n###").unwrap();
+        assert_eq!(solve_part1(&input), 1);
+        assert_eq!(solve_part2(&input), 0);
+
+        let input = input_gen(".#.\n#^.\n.#.").unwrap();
+        assert_eq!(solve_part1(&input), 2);
+        assert_eq!(solve_part2(&input), 1);
+    }
+
+    #[test]
+    fn input_gen_requires_guard() {
+        let err = input_gen("..#\n...").err().unwrap();
+        assert_eq!(err.line, 2);
+    }
+
+    #[test]
+    fn input_gen_rejects_unknown_cells() {
+        let err = input_gen("..#\n.^x").err().unwrap();
+        assert_eq!((err.line, err.column), (2, 3));
+    }
+}
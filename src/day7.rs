@@ -3,21 +3,27 @@ use std::iter::repeat_n;
 
 use aoc_runner_derive::{aoc, aoc_generator};
 
+use crate::{
+    parse::{lines, ParseError},
+    solution::{Answer, Solution, SolveError},
+};
+
 #[aoc_generator(day7)]
-pub fn input_gen(input: &str) -> Vec<(i64, Vec<i64>)> {
-    input
-        .lines()
+pub fn input_gen(input: &str) -> Result<Vec<(i64, Vec<i64>)>, ParseError> {
+    lines(7, input)
         .map(|l| {
-            let mut parts = l.split(':');
-            let sum = parts.next().unwrap().parse::<i64>().unwrap();
-            let nums = parts
-                .next()
-                .unwrap()
+            let (sum, nums) = l.split_once(':')?;
+            let sum = l.parse(sum.trim())?;
+            let nums = nums
                 .split_whitespace()
-                .map(|d| d.parse::<i64>().unwrap())
-                .collect();
+                .map(|d| l.parse(d))
+                .collect::<Result<Vec<_>, _>>()?;
 
-            (sum, nums)
+            if nums.is_empty() {
+                Err(l.error_at_end("expected at least one number after ':'"))
+            } else {
+                Ok((sum, nums))
+            }
         })
         .collect()
 }
@@ -33,8 +39,7 @@ fn permute<'a, T: Clone + Debug>(values: &'a [T], n: usize) -> impl Iterator<Ite
     let mut iters = (0..n)
         .map(move |i| {
             (0..values.len())
-                .map(move |v| repeat_n(values[v].clone(), values.len().pow(i as u32)))
-                .flatten()
+                .flat_map(move |v| repeat_n(values[v].clone(), values.len().pow(i as u32)))
                 .cycle()
         })
         .collect::<Vec<_>>();
@@ -59,9 +64,7 @@ pub fn solve_part1(input: &[(i64, Vec<i64>)]) -> i64 {
     input
         .iter()
         .filter(|(sum, nums)| {
-            permute(&[Op::Add, Op::Mul], nums.len() - 1)
-                .find(|ops| eval_ops(nums, &ops) == *sum)
-                .is_some()
+            permute(&[Op::Add, Op::Mul], nums.len() - 1).any(|ops| eval_ops(nums, &ops) == *sum)
         })
         .map(|(sum, _)| sum)
         .sum()
@@ -73,9 +76,94 @@ pub fn solve_part2(input: &[(i64, Vec<i64>)]) -> i64 {
         .iter()
         .filter(|(sum, nums)| {
             permute(&[Op::Add, Op::Mul, Op::Concat], nums.len() - 1)
-                .find(|ops| eval_ops(nums, &ops) == *sum)
-                .is_some()
+                .any(|ops| eval_ops(nums, &ops) == *sum)
         })
         .map(|(sum, _)| sum)
         .sum()
 }
+
+pub struct Day7;
+
+impl Solution for Day7 {
+    const DAY: u8 = 7;
+
+    type Input = Vec<(i64, Vec<i64>)>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_gen(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(solve_part1(input).into())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(solve_part2(input).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+190: 10 19
+3267: 81 40 27
+83: 17 5
+156: 15 6
+7290: 6 8 6 15
+161011: 16 10 13
+192: 17 8 14
+21037: 9 7 18 13
+292: 11 6 16 20
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(solve_part1(&input_gen(EXAMPLE).unwrap()), 3749);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(solve_part2(&input_gen(EXAMPLE).unwrap()), 11387);
+    }
+
+    #[test]
+    fn operators() {
+        assert_eq!(eval_ops(&[81, 40, 27], &[Op::Add, Op::Mul]), 3267);
+        assert_eq!(eval_ops(&[81, 40, 27], &[Op::Mul, Op::Add]), 3267);
+        assert_eq!(eval_ops(&[15, 6], &[Op::Concat]), 156);
+        assert_eq!(
+            eval_ops(&[6, 8, 6, 15], &[Op::Mul, Op::Concat, Op::Mul]),
+            7290
+        );
+        assert_eq!(eval_ops(&[1, 10], &[Op::Concat]), 110);
+        assert_eq!(eval_ops(&[7], &[]), 7);
+    }
+
+    #[test]
+    fn input_gen_empty() {
+        assert!(input_gen("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn input_gen_single_number() {
+        let input = input_gen("7: 7").unwrap();
+
+        assert_eq!(solve_part1(&input), 7);
+        assert_eq!(solve_part2(&input), 7);
+    }
+
+    #[test]
+    fn input_gen_max_values() {
+        let input = input_gen(&format!("{0}: {0}", i64::MAX)).unwrap();
+
+        assert_eq!(solve_part1(&input), i64::MAX);
+    }
+
+    #[test]
+    fn input_gen_rejects_missing_numbers() {
+        assert!(input_gen("190:").is_err());
+        assert!(input_gen("190 10 19").is_err());
+    }
+}
@@ -1,93 +1,54 @@
-use std::{
-    collections::{HashMap, HashSet},
-    ops::{Add, Sub},
-};
+use std::collections::{HashMap, HashSet};
 
 use aoc_runner_derive::{aoc, aoc_generator};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-pub struct Position(i64, i64);
-
-#[derive(Clone, Copy)]
-pub struct Offset(i64, i64);
-
-impl Add<Offset> for Position {
-    type Output = Position;
-
-    fn add(self, rhs: Offset) -> Self::Output {
-        Position(self.0 + rhs.0, self.1 + rhs.1)
-    }
-}
-
-impl Sub<Offset> for Position {
-    type Output = Position;
-
-    fn sub(self, rhs: Offset) -> Self::Output {
-        Position(self.0 - rhs.0, self.1 - rhs.1)
-    }
-}
-
-impl Sub<Position> for Position {
-    type Output = Offset;
-
-    fn sub(self, rhs: Position) -> Self::Output {
-        Offset(self.0 - rhs.0, self.1 - rhs.1)
-    }
-}
+use crate::{
+    geometry::Position,
+    grid::Grid,
+    parse::ParseError,
+    solution::{Answer, Solution, SolveError},
+};
 
 pub struct World {
-    map: HashMap<char, Vec<Position>>,
-    bounds: (i64, i64),
+    map: Grid<char>,
+    antennae: HashMap<char, Vec<Position>>,
 }
 
 #[aoc_generator(day8)]
-pub fn input_gen(input: &str) -> World {
-    let mut map = HashMap::new();
-
-    let height = input.lines().count() as i64;
-    let width = input.lines().next().unwrap().chars().count() as i64;
-
-    input
-        .lines()
-        .enumerate()
-        .map(|(y, l)| {
-            l.chars()
-                .enumerate()
-                .map(move |(x, c)| (Position(x as i64, y as i64), c))
-        })
-        .flatten()
-        .filter(|(_, c)| *c != '.')
-        .for_each(|(p, c)| map.entry(c).or_insert_with(Vec::new).push(p));
-
-    World {
-        map,
-        bounds: (width, height),
-    }
+pub fn input_gen(input: &str) -> Result<World, ParseError> {
+    let map = Grid::parse_with(input, |c| {
+        (c == '.' || c.is_ascii_alphanumeric()).then_some(c)
+    })
+    .map_err(|e| ParseError::from_grid(8, input, e))?;
+    let mut antennae = HashMap::new();
+
+    map.iter()
+        .filter(|(_, c)| **c != '.')
+        .for_each(|(p, c)| antennae.entry(*c).or_insert_with(Vec::new).push(p));
+
+    Ok(World { map, antennae })
 }
 
 #[aoc(day8, part1)]
 pub fn solve_part1(input: &World) -> usize {
     input
-        .map
-        .iter()
-        .map(|(_, antennae)| {
+        .antennae
+        .values()
+        .flat_map(|antennae| {
             antennae
                 .iter()
-                .map(|antenna1| {
+                .flat_map(|antenna1| {
                     antennae
                         .iter()
                         .filter(move |antenna2| antenna1 != *antenna2)
                         .map(move |antenna2| (*antenna1, *antenna2))
                 })
-                .flatten()
-                .map(|(a1, a2)| {
+                .flat_map(|(a1, a2)| {
                     let offset = a2 - a1;
                     [a1 - offset, a2 + offset].into_iter()
                 })
-                .flatten()
-                .filter(|a| a.0 >= 0 && a.1 >= 0 && a.0 < input.bounds.0 && a.1 < input.bounds.1)
+                .filter(|a| input.map.contains(*a))
         })
-        .flatten()
         .collect::<HashSet<_>>()
         .len()
 }
@@ -95,46 +56,96 @@ pub fn solve_part1(input: &World) -> usize {
 #[aoc(day8, part2)]
 pub fn solve_part2(input: &World) -> usize {
     input
-        .map
-        .iter()
-        .map(|(_, antennae)| {
+        .antennae
+        .values()
+        .flat_map(|antennae| {
             antennae
                 .iter()
-                .map(|antenna1| {
+                .flat_map(|antenna1| {
                     antennae
                         .iter()
                         .filter(move |antenna2| antenna1 != *antenna2)
                         .map(move |antenna2| (*antenna1, *antenna2))
                 })
-                .flatten()
-                .map(|(a1, a2)| {
+                .flat_map(|(a1, a2)| {
                     let offset = a2 - a1;
 
-                    (0..)
-                        .scan(a1, move |a, _| {
-                            if a.0 >= 0 && a.1 >= 0 && a.0 < input.bounds.0 && a.1 < input.bounds.1
-                            {
-                                let p = *a;
-                                *a = *a - offset;
-                                Some(p)
-                            } else {
-                                None
-                            }
-                        })
-                        .chain((0..).scan(a2, move |a, _| {
-                            *a = *a + offset;
-
-                            if a.0 >= 0 && a.1 >= 0 && a.0 < input.bounds.0 && a.1 < input.bounds.1
-                            {
-                                Some(*a)
-                            } else {
-                                None
-                            }
-                        }))
+                    input
+                        .map
+                        .ray(a1, -offset)
+                        .chain(input.map.ray(a2, offset))
+                        .map(|(p, _)| p)
                 })
-                .flatten()
         })
-        .flatten()
         .collect::<HashSet<_>>()
         .len()
 }
+
+pub struct Day8;
+
+impl Solution for Day8 {
+    const DAY: u8 = 8;
+
+    type Input = World;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_gen(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(solve_part1(input).into())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(solve_part2(input).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+............
+........0...
+.....0......
+.......0....
+....0.......
+......A.....
+............
+............
+........A...
+.........A..
+............
+............
+";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(solve_part1(&input_gen(EXAMPLE).unwrap()), 14);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(solve_part2(&input_gen(EXAMPLE).unwrap()), 34);
+    }
+
+    #[test]
+    fn input_gen_empty() {
+        assert!(input_gen("").is_err());
+    }
+
+    #[test]
+    fn input_gen_single_line() {
+        let input = input_gen("..a.a...").unwrap();
+
+        assert_eq!(solve_part1(&input), 2);
+        assert_eq!(solve_part2(&input), 4);
+    }
+
+    #[test]
+    fn input_gen_rejects_unknown_cells() {
+        let err = input_gen("..a\n.#.").err().unwrap();
+        assert_eq!((err.line, err.column), (2, 2));
+    }
+}
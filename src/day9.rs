@@ -2,9 +2,30 @@ use std::{collections::HashSet, iter::repeat_n};
 
 use aoc_runner_derive::{aoc, aoc_generator};
 
+use crate::{
+    parse::{lines, ParseError},
+    solution::{Answer, Solution, SolveError},
+};
+
 #[aoc_generator(day9)]
-pub fn input_gen(input: &str) -> Vec<u32> {
-    input.chars().map(|c| c.to_digit(10).unwrap()).collect()
+pub fn input_gen(input: &str) -> Result<Vec<u32>, ParseError> {
+    let mut lines = lines(9, input);
+    let l = lines
+        .next()
+        .ok_or_else(|| ParseError::at_end(9, input, "expected a disk map"))?;
+
+    if let Some(extra) = lines.next() {
+        return Err(extra.error_at(extra.text(), "expected the disk map on a single line"));
+    }
+
+    l.text()
+        .char_indices()
+        .map(|(idx, c)| {
+            c.to_digit(10).ok_or_else(|| {
+                l.error_at(&l.text()[idx..], format!("expected a digit, found {c:?}"))
+            })
+        })
+        .collect()
 }
 
 fn expand_disk_map(disk_map: &[u32]) -> Vec<(Option<usize>, u32)> {
@@ -63,15 +84,57 @@ pub fn solve_part1(input: &[u32]) -> usize {
     let disk = expand_disk_map(input);
     let disk = compact_disk_blocks(disk);
     disk.iter()
-        .map(|(id, size)| repeat_n(id, *size as usize))
-        .flatten()
+        .flat_map(|(id, size)| repeat_n(id, *size as usize))
         .enumerate()
         .filter_map(|(idx, id)| id.map(|id| (idx, id)))
         .map(|(idx, id)| idx * id)
         .sum()
 }
 
-fn compact_disk_spaces(mut disk: Vec<(Option<usize>, u32)>) -> Vec<(Option<usize>, u32)> {
+fn expand_blocks(disk_map: &[u32]) -> Vec<Option<usize>> {
+    expand_disk_map(disk_map)
+        .into_iter()
+        .flat_map(|(id, size)| repeat_n(id, size as usize))
+        .collect()
+}
+
+fn checksum(blocks: &[Option<usize>]) -> usize {
+    blocks
+        .iter()
+        .enumerate()
+        .filter_map(|(idx, id)| id.map(|id| idx * id))
+        .sum()
+}
+
+/// Block-by-block reference for [`solve_part1`].
+pub(crate) fn solve_part1_naive(input: &[u32]) -> usize {
+    let mut blocks = expand_blocks(input);
+    let mut free = 0;
+    let mut end = blocks.len();
+
+    loop {
+        while free < end && blocks[free].is_some() {
+            free += 1;
+        }
+        while end > free && blocks[end - 1].is_none() {
+            end -= 1;
+        }
+
+        if free >= end {
+            break checksum(&blocks);
+        }
+
+        blocks.swap(free, end - 1);
+    }
+}
+
+/// Drops empty entries and merges the free spans that were on either side
+/// of them, including around zero-length files.
+fn compact_disk_spaces(disk: Vec<(Option<usize>, u32)>) -> Vec<(Option<usize>, u32)> {
+    let mut disk = disk
+        .into_iter()
+        .filter(|(_, size)| *size > 0)
+        .collect::<Vec<_>>();
     let mut i = 0;
 
     while i + 1 < disk.len() {
@@ -83,10 +146,11 @@ fn compact_disk_spaces(mut disk: Vec<(Option<usize>, u32)>) -> Vec<(Option<usize
         }
     }
 
-    disk.into_iter().filter(|(_, size)| *size > 0).collect()
+    disk
 }
 
-fn compact_disk_files(mut disk: Vec<(Option<usize>, u32)>) -> Vec<(Option<usize>, u32)> {
+fn compact_disk_files(disk: Vec<(Option<usize>, u32)>) -> Vec<(Option<usize>, u32)> {
+    let mut disk = compact_disk_spaces(disk);
     let mut checked = HashSet::new();
     loop {
         let checked2 = checked.clone();
@@ -125,15 +189,118 @@ fn compact_disk_files(mut disk: Vec<(Option<usize>, u32)>) -> Vec<(Option<usize>
     .collect()
 }
 
+/// Block-by-block reference for [`solve_part2`].
+pub(crate) fn solve_part2_naive(input: &[u32]) -> usize {
+    let mut blocks = expand_blocks(input);
+
+    for id in (0..input.len().div_ceil(2)).rev() {
+        let size = input[id * 2] as usize;
+        let Some(start) = blocks.iter().position(|b| *b == Some(id)) else {
+            continue;
+        };
+
+        let mut run = 0;
+        for idx in 0..start {
+            run = if blocks[idx].is_none() { run + 1 } else { 0 };
+
+            if run == size {
+                let dst = idx + 1 - size;
+                blocks[dst..dst + size].fill(Some(id));
+                blocks[start..start + size].fill(None);
+                break;
+            }
+        }
+    }
+
+    checksum(&blocks)
+}
+
 #[aoc(day9, part2)]
 pub fn solve_part2(input: &[u32]) -> usize {
     let disk = expand_disk_map(input);
     let disk = compact_disk_files(disk);
     disk.iter()
-        .map(|(id, size)| repeat_n(id, *size as usize))
-        .flatten()
+        .flat_map(|(id, size)| repeat_n(id, *size as usize))
         .enumerate()
         .filter_map(|(idx, id)| id.map(|id| (idx, id)))
         .map(|(idx, id)| idx * id)
         .sum()
 }
+
+pub struct Day9;
+
+impl Solution for Day9 {
+    const DAY: u8 = 9;
+
+    type Input = Vec<u32>;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError> {
+        input_gen(input)
+    }
+
+    fn part1(input: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(solve_part1(input).into())
+    }
+
+    fn part2(input: &Self::Input) -> Result<Answer, SolveError> {
+        Ok(solve_part2(input).into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "2333133121414131402";
+
+    #[test]
+    fn part1_example() {
+        assert_eq!(solve_part1(&input_gen(EXAMPLE).unwrap()), 1928);
+    }
+
+    #[test]
+    fn part2_example() {
+        assert_eq!(solve_part2(&input_gen(EXAMPLE).unwrap()), 2858);
+    }
+
+    #[test]
+    fn zero_length_files() {
+        assert_eq!(solve_part2(&[3, 2, 0, 3, 3, 1]), 24);
+        assert_eq!(solve_part2(&[3, 2, 0, 2, 3, 3, 1, 0, 3]), 114);
+        assert_eq!(solve_part1(&[3, 2, 0, 3, 3, 1]), 24);
+    }
+
+    #[test]
+    fn input_gen_trailing_newline() {
+        assert_eq!(input_gen("12345\r\n").unwrap(), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn input_gen_empty() {
+        assert!(input_gen("").is_err());
+    }
+
+    #[test]
+    fn input_gen_single_file() {
+        let input = input_gen("9").unwrap();
+
+        assert_eq!(solve_part1(&input), 0);
+        assert_eq!(solve_part2(&input), 0);
+    }
+
+    #[test]
+    fn input_gen_max_sizes() {
+        let input = input_gen("909").unwrap();
+
+        assert_eq!(solve_part1(&input), (9..18).sum::<usize>());
+        assert_eq!(solve_part2(&input), (9..18).sum::<usize>());
+    }
+
+    #[test]
+    fn input_gen_rejects_non_digits() {
+        let err = input_gen("12a4").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 3));
+
+        assert!(input_gen("12\n34").is_err());
+    }
+}
@@ -0,0 +1,380 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+/// A point on the integer plane. `y` grows downwards, matching how puzzle
+/// maps are read line by line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Position(pub i64, pub i64);
+
+/// The difference between two [`Position`]s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Offset(pub i64, pub i64);
+
+impl Position {
+    pub const ORIGIN: Position = Position(0, 0);
+
+    pub fn manhattan_distance(self, other: Position) -> i64 {
+        (self - other).manhattan()
+    }
+
+    pub fn chebyshev_distance(self, other: Position) -> i64 {
+        (self - other).chebyshev()
+    }
+}
+
+impl Offset {
+    pub const ZERO: Offset = Offset(0, 0);
+
+    /// Rotates a quarter turn clockwise.
+    pub fn rotate_right(self) -> Offset {
+        Offset(-self.1, self.0)
+    }
+
+    /// Rotates a quarter turn counter-clockwise.
+    pub fn rotate_left(self) -> Offset {
+        Offset(self.1, -self.0)
+    }
+
+    pub fn reverse(self) -> Offset {
+        -self
+    }
+
+    pub fn manhattan(self) -> i64 {
+        self.0.abs() + self.1.abs()
+    }
+
+    pub fn chebyshev(self) -> i64 {
+        self.0.abs().max(self.1.abs())
+    }
+}
+
+impl Add<Offset> for Position {
+    type Output = Position;
+
+    fn add(self, rhs: Offset) -> Self::Output {
+        Position(self.0 + rhs.0, self.1 + rhs.1)
+    }
+}
+
+impl AddAssign<Offset> for Position {
+    fn add_assign(&mut self, rhs: Offset) {
+        *self = *self + rhs;
+    }
+}
+
+impl Sub<Offset> for Position {
+    type Output = Position;
+
+    fn sub(self, rhs: Offset) -> Self::Output {
+        Position(self.0 - rhs.0, self.1 - rhs.1)
+    }
+}
+
+impl SubAssign<Offset> for Position {
+    fn sub_assign(&mut self, rhs: Offset) {
+        *self = *self - rhs;
+    }
+}
+
+impl Sub<Position> for Position {
+    type Output = Offset;
+
+    fn sub(self, rhs: Position) -> Self::Output {
+        Offset(self.0 - rhs.0, self.1 - rhs.1)
+    }
+}
+
+impl Add<Offset> for Offset {
+    type Output = Offset;
+
+    fn add(self, rhs: Offset) -> Self::Output {
+        Offset(self.0 + rhs.0, self.1 + rhs.1)
+    }
+}
+
+impl Sub<Offset> for Offset {
+    type Output = Offset;
+
+    fn sub(self, rhs: Offset) -> Self::Output {
+        Offset(self.0 - rhs.0, self.1 - rhs.1)
+    }
+}
+
+impl Mul<i64> for Offset {
+    type Output = Offset;
+
+    fn mul(self, rhs: i64) -> Self::Output {
+        Offset(self.0 * rhs, self.1 * rhs)
+    }
+}
+
+impl Neg for Offset {
+    type Output = Offset;
+
+    fn neg(self) -> Self::Output {
+        Offset(-self.0, -self.1)
+    }
+}
+
+/// An axis-aligned rectangle, inclusive on both corners.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BoundingBox {
+    pub min: Position,
+    pub max: Position,
+}
+
+impl BoundingBox {
+    pub fn new(a: Position, b: Position) -> Self {
+        Self {
+            min: Position(a.0.min(b.0), a.1.min(b.1)),
+            max: Position(a.0.max(b.0), a.1.max(b.1)),
+        }
+    }
+
+    /// The smallest box containing every point, or `None` if there are none.
+    pub fn from_points(points: impl IntoIterator<Item = Position>) -> Option<Self> {
+        let mut points = points.into_iter();
+        let first = points.next()?;
+
+        Some(points.fold(Self::new(first, first), |b, p| b.extend(p)))
+    }
+
+    pub fn extend(self, p: Position) -> Self {
+        Self::new(
+            Position(self.min.0.min(p.0), self.min.1.min(p.1)),
+            Position(self.max.0.max(p.0), self.max.1.max(p.1)),
+        )
+    }
+
+    pub fn width(&self) -> i64 {
+        self.max.0 - self.min.0 + 1
+    }
+
+    pub fn height(&self) -> i64 {
+        self.max.1 - self.min.1 + 1
+    }
+
+    pub fn contains(&self, p: Position) -> bool {
+        p.0 >= self.min.0 && p.1 >= self.min.1 && p.0 <= self.max.0 && p.1 <= self.max.1
+    }
+}
+
+/// The four cardinal directions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    /// Clockwise, starting from up.
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    pub fn offset(self) -> Offset {
+        match self {
+            Direction::Up => Offset(0, -1),
+            Direction::Right => Offset(1, 0),
+            Direction::Down => Offset(0, 1),
+            Direction::Left => Offset(-1, 0),
+        }
+    }
+
+    pub fn turn_right(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Right => Direction::Down,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+        }
+    }
+
+    pub fn turn_left(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Left,
+            Direction::Right => Direction::Up,
+            Direction::Down => Direction::Right,
+            Direction::Left => Direction::Down,
+        }
+    }
+
+    pub fn reverse(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Right => Direction::Left,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+        }
+    }
+}
+
+/// The cardinal and intercardinal directions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Direction8 {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction8 {
+    /// Clockwise, starting from up.
+    pub const ALL: [Direction8; 8] = [
+        Direction8::Up,
+        Direction8::UpRight,
+        Direction8::Right,
+        Direction8::DownRight,
+        Direction8::Down,
+        Direction8::DownLeft,
+        Direction8::Left,
+        Direction8::UpLeft,
+    ];
+
+    pub fn offset(self) -> Offset {
+        match self {
+            Direction8::Up => Offset(0, -1),
+            Direction8::UpRight => Offset(1, -1),
+            Direction8::Right => Offset(1, 0),
+            Direction8::DownRight => Offset(1, 1),
+            Direction8::Down => Offset(0, 1),
+            Direction8::DownLeft => Offset(-1, 1),
+            Direction8::Left => Offset(-1, 0),
+            Direction8::UpLeft => Offset(-1, -1),
+        }
+    }
+
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|d| *d == self).unwrap()
+    }
+
+    /// Rotates by `eighths` of a turn, clockwise for positive values.
+    pub fn rotate(self, eighths: i64) -> Direction8 {
+        Self::ALL[(self.index() as i64 + eighths).rem_euclid(8) as usize]
+    }
+
+    pub fn turn_right(self) -> Direction8 {
+        self.rotate(2)
+    }
+
+    pub fn turn_left(self) -> Direction8 {
+        self.rotate(-2)
+    }
+
+    pub fn reverse(self) -> Direction8 {
+        self.rotate(4)
+    }
+
+    pub fn is_diagonal(self) -> bool {
+        self.index() % 2 == 1
+    }
+}
+
+impl From<Direction> for Direction8 {
+    fn from(d: Direction) -> Self {
+        match d {
+            Direction::Up => Direction8::Up,
+            Direction::Right => Direction8::Right,
+            Direction::Down => Direction8::Down,
+            Direction::Left => Direction8::Left,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn offset_rotations() {
+        assert_eq!(Offset(1, 0).rotate_right(), Offset(0, 1));
+        assert_eq!(Offset(1, 0).rotate_left(), Offset(0, -1));
+        assert_eq!(Offset(2, -3).rotate_right(), Offset(3, 2));
+
+        for d in Direction::ALL {
+            assert_eq!(d.offset().rotate_right(), d.turn_right().offset());
+            assert_eq!(d.offset().rotate_left(), d.turn_left().offset());
+            assert_eq!(d.offset().reverse(), d.reverse().offset());
+        }
+
+        let o = Offset(5, -7);
+        assert_eq!(o.rotate_left().rotate_right(), o);
+        assert_eq!(
+            o.rotate_right()
+                .rotate_right()
+                .rotate_right()
+                .rotate_right(),
+            o
+        );
+    }
+
+    #[test]
+    fn direction8_rotations() {
+        assert_eq!(Direction8::Up.rotate(1), Direction8::UpRight);
+        assert_eq!(Direction8::Up.rotate(-1), Direction8::UpLeft);
+        assert_eq!(Direction8::Left.rotate(11), Direction8::UpRight);
+        assert_eq!(Direction8::UpRight.turn_right(), Direction8::DownRight);
+        assert_eq!(Direction8::UpRight.turn_left(), Direction8::UpLeft);
+
+        for d in Direction8::ALL {
+            assert_eq!(d.turn_right().offset(), d.offset().rotate_right());
+            assert_eq!(d.turn_left().offset(), d.offset().rotate_left());
+            assert_eq!(d.reverse().offset(), -d.offset());
+            assert_eq!(d.rotate(8), d);
+            assert_eq!(d.rotate(-3).rotate(3), d);
+        }
+
+        for d in Direction::ALL {
+            assert_eq!(Direction8::from(d).turn_right(), d.turn_right().into());
+            assert_eq!(Direction8::from(d).turn_left(), d.turn_left().into());
+        }
+    }
+
+    #[test]
+    fn diagonals() {
+        let diagonal = Direction8::ALL
+            .into_iter()
+            .filter(|d| d.is_diagonal())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            diagonal,
+            [
+                Direction8::UpRight,
+                Direction8::DownRight,
+                Direction8::DownLeft,
+                Direction8::UpLeft
+            ]
+        );
+        for d in Direction8::ALL {
+            let Offset(dx, dy) = d.offset();
+            assert_eq!(d.is_diagonal(), dx != 0 && dy != 0);
+            assert_eq!(d.offset().chebyshev(), 1);
+        }
+    }
+
+    #[test]
+    fn bounding_box_from_points() {
+        assert_eq!(BoundingBox::from_points([]), None);
+
+        let single = BoundingBox::from_points([Position(2, 3)]).unwrap();
+        assert_eq!((single.width(), single.height()), (1, 1));
+
+        let points = [Position(3, -1), Position(-2, 4), Position(0, 0)];
+        let bounds = BoundingBox::from_points(points).unwrap();
+
+        assert_eq!(bounds, BoundingBox::new(Position(-2, -1), Position(3, 4)));
+        assert_eq!((bounds.width(), bounds.height()), (6, 6));
+        assert!(points.iter().all(|p| bounds.contains(*p)));
+        assert!(!bounds.contains(Position(4, 0)));
+        assert!(!bounds.contains(Position(0, -2)));
+    }
+}
@@ -0,0 +1,281 @@
+use std::{
+    fmt,
+    ops::{Index, IndexMut},
+    str::FromStr,
+};
+
+use crate::geometry::{BoundingBox, Direction, Direction8, Offset, Position};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GridError {
+    Empty,
+    Ragged {
+        line: usize,
+        expected: usize,
+        found: usize,
+    },
+    InvalidCell {
+        line: usize,
+        column: usize,
+        cell: char,
+    },
+}
+
+impl fmt::Display for GridError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GridError::Empty => write!(f, "grid is empty"),
+            GridError::Ragged {
+                line,
+                expected,
+                found,
+            } => write!(
+                f,
+                "line {line} has {found} cells, expected {expected} like the first line"
+            ),
+            GridError::InvalidCell { line, column, cell } => {
+                write!(f, "invalid cell {cell:?} at line {line}, column {column}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GridError {}
+
+/// A rectangular grid stored row-major in a single `Vec`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    width: usize,
+    height: usize,
+    cells: Vec<T>,
+}
+
+impl<T> Grid<T> {
+    pub fn new(width: usize, height: usize, cells: Vec<T>) -> Self {
+        assert_eq!(width * height, cells.len());
+
+        Self {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    pub fn filled(width: usize, height: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self::new(width, height, vec![value; width * height])
+    }
+
+    /// Parses one cell per character, one row per line, ignoring trailing
+    /// blank lines. Line and column numbers in the returned errors start at 1.
+    pub fn parse_with<F>(input: &str, mut f: F) -> Result<Self, GridError>
+    where
+        F: FnMut(char) -> Option<T>,
+    {
+        let mut width = None;
+        let mut height = 0;
+        let mut cells = Vec::new();
+
+        for (y, line) in input.trim_end().lines().enumerate() {
+            let mut found = 0;
+
+            for (x, c) in line.chars().enumerate() {
+                cells.push(f(c).ok_or(GridError::InvalidCell {
+                    line: y + 1,
+                    column: x + 1,
+                    cell: c,
+                })?);
+                found += 1;
+            }
+
+            let expected = *width.get_or_insert(found);
+            if found != expected {
+                return Err(GridError::Ragged {
+                    line: y + 1,
+                    expected,
+                    found,
+                });
+            }
+
+            height += 1;
+        }
+
+        match width {
+            Some(width) if width > 0 => Ok(Self::new(width, height, cells)),
+            _ => Err(GridError::Empty),
+        }
+    }
+
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid::new(self.width, self.height, self.cells.iter().map(f).collect())
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(
+            Position::ORIGIN,
+            Position(self.width as i64 - 1, self.height as i64 - 1),
+        )
+    }
+
+    pub fn contains(&self, p: Position) -> bool {
+        p.0 >= 0 && p.1 >= 0 && (p.0 as usize) < self.width && (p.1 as usize) < self.height
+    }
+
+    fn index_of(&self, p: Position) -> Option<usize> {
+        self.contains(p)
+            .then(|| p.1 as usize * self.width + p.0 as usize)
+    }
+
+    pub fn get(&self, p: Position) -> Option<&T> {
+        self.index_of(p).map(|idx| &self.cells[idx])
+    }
+
+    pub fn get_mut(&mut self, p: Position) -> Option<&mut T> {
+        self.index_of(p).map(|idx| &mut self.cells[idx])
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let width = self.width;
+
+        (0..self.cells.len()).map(move |idx| Position((idx % width) as i64, (idx / width) as i64))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.positions().zip(self.cells.iter())
+    }
+
+    /// The in-bounds orthogonal neighbours of `p`, clockwise from up.
+    pub fn neighbours4(&self, p: Position) -> impl Iterator<Item = (Position, &T)> {
+        self.neighbours(p, Direction::ALL.map(Direction::offset))
+    }
+
+    /// The in-bounds neighbours of `p` including diagonals, clockwise from up.
+    pub fn neighbours8(&self, p: Position) -> impl Iterator<Item = (Position, &T)> {
+        self.neighbours(p, Direction8::ALL.map(Direction8::offset))
+    }
+
+    fn neighbours(
+        &self,
+        p: Position,
+        offsets: impl IntoIterator<Item = Offset>,
+    ) -> impl Iterator<Item = (Position, &T)> {
+        offsets
+            .into_iter()
+            .map(move |o| p + o)
+            .filter_map(|n| self.get(n).map(|v| (n, v)))
+    }
+
+    /// Walks from `start` (inclusive) in steps of `step` until leaving the grid.
+    pub fn ray(&self, start: Position, step: Offset) -> impl Iterator<Item = (Position, &T)> {
+        std::iter::successors(Some(start), move |p| Some(*p + step))
+            .map_while(|p| self.get(p).map(|v| (p, v)))
+    }
+
+    pub fn row(&self, y: usize) -> impl Iterator<Item = (Position, &T)> {
+        self.ray(Position(0, y as i64), Offset(1, 0))
+    }
+
+    pub fn column(&self, x: usize) -> impl Iterator<Item = (Position, &T)> {
+        self.ray(Position(x as i64, 0), Offset(0, 1))
+    }
+
+    /// Every maximal run through the grid in the given direction, each one
+    /// starting on the edge the direction points away from.
+    pub fn rays(&self, step: Offset) -> impl Iterator<Item = impl Iterator<Item = (Position, &T)>> {
+        self.positions()
+            .filter(move |p| !self.contains(*p - step))
+            .map(move |p| self.ray(p, step))
+    }
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, p: Position) -> &Self::Output {
+        self.get(p).expect("position out of bounds")
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, p: Position) -> &mut Self::Output {
+        self.get_mut(p).expect("position out of bounds")
+    }
+}
+
+impl FromStr for Grid<char> {
+    type Err = GridError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Grid::parse_with(s, Some)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cells<'a>(cells: impl Iterator<Item = (Position, &'a char)>) -> Vec<(i64, i64, char)> {
+        cells.map(|(p, c)| (p.0, p.1, *c)).collect()
+    }
+
+    #[test]
+    fn filled() {
+        let grid = Grid::filled(3, 2, '.');
+
+        assert_eq!((grid.width(), grid.height()), (3, 2));
+        assert_eq!(grid.iter().count(), 6);
+        assert!(grid.iter().all(|(_, c)| *c == '.'));
+        assert_eq!(grid, "...\n...".parse().unwrap());
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let grid = "abc\ndef".parse::<Grid<char>>().unwrap();
+
+        assert_eq!(cells(grid.row(1)), [(0, 1, 'd'), (1, 1, 'e'), (2, 1, 'f')]);
+        assert_eq!(cells(grid.column(2)), [(2, 0, 'c'), (2, 1, 'f')]);
+        assert_eq!(cells(grid.row(2)), []);
+        assert_eq!(cells(grid.column(3)), []);
+    }
+
+    #[test]
+    fn neighbours() {
+        let grid = "abc\ndef\nghi".parse::<Grid<char>>().unwrap();
+
+        assert_eq!(
+            cells(grid.neighbours8(Position(1, 1))),
+            [
+                (1, 0, 'b'),
+                (2, 0, 'c'),
+                (2, 1, 'f'),
+                (2, 2, 'i'),
+                (1, 2, 'h'),
+                (0, 2, 'g'),
+                (0, 1, 'd'),
+                (0, 0, 'a')
+            ]
+        );
+        assert_eq!(
+            cells(grid.neighbours8(Position(0, 0))),
+            [(1, 0, 'b'), (1, 1, 'e'), (0, 1, 'd')]
+        );
+        assert_eq!(
+            cells(grid.neighbours4(Position(2, 2))),
+            [(2, 1, 'f'), (1, 2, 'h')]
+        );
+        assert_eq!(cells(grid.neighbours8(Position(3, 3))), [(2, 2, 'i')]);
+    }
+}
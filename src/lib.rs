@@ -1,6 +1,7 @@
-use aoc_runner;
 use aoc_runner_derive::aoc_lib;
 
+pub mod bench;
+pub mod crosscheck;
 pub mod day1;
 pub mod day10;
 pub mod day11;
@@ -12,5 +13,12 @@ pub mod day6;
 pub mod day7;
 pub mod day8;
 pub mod day9;
+pub mod geometry;
+pub mod grid;
+pub mod parse;
+pub mod rng;
+pub mod solution;
+pub mod synth;
+pub mod verify;
 
 aoc_lib! { year = 2024 }
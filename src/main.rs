@@ -1,5 +1,662 @@
-use aoc_2024;
-use aoc_runner;
-use aoc_runner_derive::aoc_main;
+use std::{
+    env, fs,
+    io::{self, Read},
+    process::ExitCode,
+    time::Duration,
+};
 
-aoc_main! { lib = aoc_2024 }
+use aoc_2024::{
+    bench::{self, BenchReport, CountingAlloc},
+    crosscheck, day1, day2, day3,
+    rng::Rng,
+    solution::{self, Answer, Solver, Timed},
+    synth::{self, Params},
+    verify::{Manifest, Report, Status},
+};
+use serde_json::{json, Value};
+
+const USAGE: &str = "\
+Usage:
+    aoc-2024 run <day> [<part>] [--input <path>] [--json]
+    aoc-2024 all [--input-dir <dir>] [--json]
+    aoc-2024 verify [--manifest <path>] [--json]
+    aoc-2024 cross-check <day> [--cases <n>] [--seed <n>]
+    aoc-2024 generate <day> [--size <n>] [--density <p>] [--seed <n>]
+    aoc-2024 bench [<day>] [--runs <n>] [--input-dir <dir> | --synthetic] [--json]
+    aoc-2024 bench-diff <baseline> <current> [--threshold <p>]
+    aoc-2024 explain <day> [--input <path>] [--audit | --render <format>]
+
+Options:
+    --input <path>      Read the puzzle input from <path>, or stdin if <path> is -.
+                        Defaults to <input-dir>/day<day>.txt.
+    --input-dir <dir>   Directory holding day<N>.txt inputs [default: input/2024].
+    --manifest <path>   Expected answers to check [default: answers.toml].
+    --cases <n>         Generated inputs per cross-check [default: 1000].
+    --seed <n>          Seed for the first generated input [default: 0].
+    --size <n>          Size of the generated input [default: 100].
+    --density <p>       Density of the generated input, 0 to 1 [default: 0.5].
+    --runs <n>          Times to run each generator and part [default: 10].
+    --synthetic         Benchmark generated inputs instead of the input files,
+                        using --size, --density and --seed.
+    --threshold <p>     Relative growth reported as a regression [default: 0.1].
+    --audit             For day 3, list malformed mul instructions and why.
+    --render <format>   For day 3, print the memory with the instructions part 2
+                        runs highlighted and disabled regions dimmed, as ansi
+                        or html.
+    --json              Print results as JSON instead of text.
+    -h, --help          Print this help.";
+
+#[global_allocator]
+static ALLOCATOR: CountingAlloc = CountingAlloc;
+
+const DEFAULT_INPUT_DIR: &str = "input/2024";
+const DEFAULT_MANIFEST: &str = "answers.toml";
+
+enum Command {
+    Run { day: u8, part: Option<u8> },
+    All,
+    Verify,
+    CrossCheck { day: u8 },
+    Generate { day: u8 },
+    Bench { day: Option<u8> },
+    BenchDiff { baseline: String, current: String },
+    Explain { day: u8 },
+}
+
+struct Options {
+    command: Command,
+    input: Option<String>,
+    input_dir: String,
+    manifest: String,
+    cases: u64,
+    seed: u64,
+    params: Params,
+    runs: u32,
+    synthetic: bool,
+    threshold: f64,
+    audit: bool,
+    render: Option<day3::Format>,
+    json: bool,
+}
+
+fn parse_number<T: std::str::FromStr>(value: Option<String>, what: &str) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("missing {what}"))?;
+
+    value
+        .parse()
+        .map_err(|_| format!("invalid {what} {value:?}"))
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let mut positional = Vec::new();
+    let mut input = None;
+    let mut input_dir = DEFAULT_INPUT_DIR.to_string();
+    let mut manifest = DEFAULT_MANIFEST.to_string();
+    let mut cases = 1000;
+    let mut seed = 0;
+    let mut params = Params::default();
+    let mut runs = 10;
+    let mut synthetic = false;
+    let mut threshold = 0.1;
+    let mut audit = false;
+    let mut render = None;
+    let mut json = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--synthetic" => synthetic = true,
+            "--audit" => audit = true,
+            "--input" => input = Some(args.next().ok_or("missing value for --input")?),
+            "--input-dir" => input_dir = args.next().ok_or("missing value for --input-dir")?,
+            "--manifest" => manifest = args.next().ok_or("missing value for --manifest")?,
+            "--cases" => cases = parse_number(args.next(), "value for --cases")?,
+            "--seed" => seed = parse_number(args.next(), "value for --seed")?,
+            "--size" => params.size = parse_number(args.next(), "value for --size")?,
+            "--density" => params.density = parse_number(args.next(), "value for --density")?,
+            "--runs" => runs = parse_number(args.next(), "value for --runs")?,
+            "--threshold" => threshold = parse_number(args.next(), "value for --threshold")?,
+            "--render" => render = Some(parse_number(args.next(), "value for --render")?),
+            "-h" | "--help" => return Err(String::new()),
+            _ if arg.starts_with('-') && arg != "-" => return Err(format!("unknown option {arg}")),
+            _ => positional.push(arg),
+        }
+    }
+
+    let mut positional = positional.into_iter();
+    let command = match positional.next().as_deref() {
+        Some("run") => Command::Run {
+            day: parse_number(positional.next(), "day")?,
+            part: positional
+                .next()
+                .map(|part| parse_number(Some(part), "part"))
+                .transpose()?,
+        },
+        Some("all") => Command::All,
+        Some("verify") => Command::Verify,
+        Some("cross-check") => Command::CrossCheck {
+            day: parse_number(positional.next(), "day")?,
+        },
+        Some("generate") => Command::Generate {
+            day: parse_number(positional.next(), "day")?,
+        },
+        Some("bench") => Command::Bench {
+            day: positional
+                .next()
+                .map(|day| parse_number(Some(day), "day"))
+                .transpose()?,
+        },
+        Some("bench-diff") => Command::BenchDiff {
+            baseline: positional.next().ok_or("missing baseline report")?,
+            current: positional.next().ok_or("missing current report")?,
+        },
+        Some("explain") => Command::Explain {
+            day: parse_number(positional.next(), "day")?,
+        },
+        Some(other) => return Err(format!("unknown command {other:?}")),
+        None => return Err("missing command".to_string()),
+    };
+
+    if let Some(extra) = positional.next() {
+        return Err(format!("unexpected argument {extra:?}"));
+    }
+
+    if input.is_some() && !matches!(command, Command::Run { .. } | Command::Explain { .. }) {
+        return Err("--input only applies to run and explain, use --input-dir".to_string());
+    }
+
+    if audit && !matches!(command, Command::Explain { .. }) {
+        return Err("--audit only applies to explain".to_string());
+    }
+
+    if render.is_some() && !matches!(command, Command::Explain { .. }) {
+        return Err("--render only applies to explain".to_string());
+    }
+
+    if audit && render.is_some() {
+        return Err("--audit and --render cannot be combined".to_string());
+    }
+
+    Ok(Options {
+        command,
+        input,
+        input_dir,
+        manifest,
+        cases,
+        seed,
+        params,
+        runs,
+        synthetic,
+        threshold,
+        audit,
+        render,
+        json,
+    })
+}
+
+fn read_input(path: &str) -> Result<String, String> {
+    if path == "-" {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|e| format!("cannot read stdin: {e}"))?;
+
+        Ok(input)
+    } else {
+        fs::read_to_string(path).map_err(|e| format!("cannot read {path}: {e}"))
+    }
+}
+
+struct Outcome {
+    solver: &'static Solver,
+    result: Result<Timed, String>,
+}
+
+/// Runs each solver, reading every day's input only once.
+fn run(solvers: &[&'static Solver], input_path: impl Fn(u8) -> String) -> Vec<Outcome> {
+    let mut input = None;
+
+    solvers
+        .iter()
+        .map(|solver| {
+            if !matches!(input, Some((day, _)) if day == solver.day) {
+                input = Some((solver.day, read_input(&input_path(solver.day))));
+            }
+
+            let result = match &input {
+                Some((_, Ok(input))) => solver.run_timed(input).map_err(|e| e.to_string()),
+                Some((_, Err(e))) => Err(e.clone()),
+                None => unreachable!(),
+            };
+
+            Outcome { solver, result }
+        })
+        .collect()
+}
+
+fn answer_to_json(answer: &Answer) -> Value {
+    match answer {
+        Answer::Number(n) => i64::try_from(*n)
+            .map(Value::from)
+            .unwrap_or_else(|_| Value::from(n.to_string())),
+        Answer::Text(s) => Value::from(s.as_str()),
+    }
+}
+
+fn print_json(outcomes: &[Outcome]) {
+    let results = outcomes
+        .iter()
+        .map(|o| match &o.result {
+            Ok(timed) => json!({
+                "day": o.solver.day,
+                "part": o.solver.part,
+                "answer": answer_to_json(&timed.answer),
+                "parse_ns": timed.parse.as_nanos() as u64,
+                "solve_ns": timed.solve.as_nanos() as u64,
+            }),
+            Err(e) => json!({
+                "day": o.solver.day,
+                "part": o.solver.part,
+                "error": e,
+            }),
+        })
+        .collect::<Vec<_>>();
+
+    println!("{}", serde_json::to_string_pretty(&results).unwrap());
+}
+
+fn print_text(outcomes: &[Outcome]) {
+    for o in outcomes {
+        let label = format!("day {:>2}, part {}", o.solver.day, o.solver.part);
+
+        match &o.result {
+            Ok(timed) => println!(
+                "{label}: {:<20} (parse {:.2?}, solve {:.2?})",
+                timed.answer.to_string(),
+                timed.parse,
+                timed.solve
+            ),
+            Err(e) => eprintln!("{label}: error: {e}"),
+        }
+    }
+}
+
+fn print_verify_json(manifest: &Manifest, reports: &[Report]) {
+    let results = reports
+        .iter()
+        .map(|r| {
+            let mut result = json!({
+                "day": r.case.day,
+                "part": r.case.part,
+                "input": manifest.input_path(r.case),
+                "expected": r.case.answer.to_string(),
+            });
+
+            match &r.status {
+                Status::Pass => result["status"] = "pass".into(),
+                Status::Mismatch { actual } => {
+                    result["status"] = "mismatch".into();
+                    result["actual"] = actual.to_string().into();
+                }
+                Status::Fail(reason) => {
+                    result["status"] = "fail".into();
+                    result["error"] = reason.as_str().into();
+                }
+            }
+
+            if let Some(timed) = &r.timing {
+                result["parse_ns"] = (timed.parse.as_nanos() as u64).into();
+                result["solve_ns"] = (timed.solve.as_nanos() as u64).into();
+            }
+
+            result
+        })
+        .collect::<Vec<_>>();
+
+    println!("{}", serde_json::to_string_pretty(&results).unwrap());
+}
+
+fn print_verify_text(manifest: &Manifest, reports: &[Report]) {
+    for r in reports {
+        let label = format!(
+            "day {:>2}, part {} ({})",
+            r.case.day,
+            r.case.part,
+            manifest.input_path(r.case).display()
+        );
+        let timing = r
+            .timing
+            .as_ref()
+            .map(|t| format!(" (parse {:.2?}, solve {:.2?})", t.parse, t.solve))
+            .unwrap_or_default();
+
+        match &r.status {
+            Status::Pass => println!("PASS     {label}: {}{timing}", r.case.answer),
+            Status::Mismatch { actual } => println!(
+                "MISMATCH {label}: expected {}, got {actual}{timing}",
+                r.case.answer
+            ),
+            Status::Fail(reason) => println!("FAIL     {label}: {reason}"),
+        }
+    }
+
+    let passed = reports
+        .iter()
+        .filter(|r| matches!(r.status, Status::Pass))
+        .count();
+    println!("\n{passed}/{} cases passed", reports.len());
+}
+
+fn verify(options: &Options) -> ExitCode {
+    let manifest = match Manifest::load(&options.manifest) {
+        Ok(manifest) => manifest,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let reports = manifest.verify();
+
+    if options.json {
+        print_verify_json(&manifest, &reports);
+    } else {
+        print_verify_text(&manifest, &reports);
+    }
+
+    if reports.iter().all(|r| matches!(r.status, Status::Pass)) {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn cross_check(day: u8, options: &Options) -> ExitCode {
+    let checks = crosscheck::checks()
+        .iter()
+        .filter(|c| c.day == day)
+        .collect::<Vec<_>>();
+
+    if checks.is_empty() {
+        eprintln!("error: no cross-checks for day {day}");
+        return ExitCode::FAILURE;
+    }
+
+    let mut ok = true;
+    for check in checks {
+        match check.run(options.cases, options.seed) {
+            None => println!("ok       day {day} {}: {} cases", check.name, options.cases),
+            Some(d) => {
+                ok = false;
+                println!("DIVERGED day {day} {} (seed {}):", check.name, d.seed);
+                println!("    input:     {}", d.input);
+                println!("    reference: {}", d.reference);
+                println!("    optimized: {}", d.optimized);
+            }
+        }
+    }
+
+    if ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn generate(day: u8, options: &Options) -> ExitCode {
+    match synth::generate(day, &mut Rng::new(options.seed), &options.params) {
+        Some(input) => {
+            print!("{input}");
+            ExitCode::SUCCESS
+        }
+        None => {
+            eprintln!("error: no generator for day {day}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn print_bench_text(report: &BenchReport) {
+    println!(
+        "{:<16} {:>12} {:>12} {:>12} {:>8} {:>12}",
+        "", "min", "median", "max", "allocs", "bytes"
+    );
+
+    for e in &report.entries {
+        println!(
+            "day {:>2}, {:<8} {:>12.2?} {:>12.2?} {:>12.2?} {:>8} {:>12}",
+            e.day,
+            e.stage.to_string(),
+            Duration::from_nanos(e.nanos.min),
+            Duration::from_nanos(e.nanos.median),
+            Duration::from_nanos(e.nanos.max),
+            e.allocations,
+            e.allocated_bytes
+        );
+    }
+}
+
+fn bench(day: Option<u8>, options: &Options) -> ExitCode {
+    let mut days = solution::solvers()
+        .iter()
+        .map(|s| s.day)
+        .collect::<Vec<_>>();
+    days.dedup();
+    if let Some(day) = day {
+        days.retain(|d| *d == day);
+    }
+
+    if let (Some(day), true) = (day, days.is_empty()) {
+        eprintln!("error: no solver for day {day}");
+        return ExitCode::FAILURE;
+    }
+
+    let mut report = BenchReport {
+        runs: options.runs.max(1),
+        entries: Vec::new(),
+    };
+    let mut ok = true;
+
+    for day in days {
+        let input = if options.synthetic {
+            synth::generate(day, &mut Rng::new(options.seed), &options.params)
+                .ok_or_else(|| format!("no generator for day {day}"))
+        } else {
+            read_input(&format!("{}/day{day}.txt", options.input_dir))
+        };
+
+        let entries = input.and_then(|input| {
+            bench::bench_day(day, &input, options.runs).map_err(|e| e.to_string())
+        });
+
+        match entries {
+            Ok(entries) => report.entries.extend(entries),
+            Err(e) => {
+                ok = false;
+                eprintln!("day {day:>2}: error: {e}");
+            }
+        }
+    }
+
+    if options.json {
+        println!("{}", report.to_json());
+    } else {
+        print_bench_text(&report);
+    }
+
+    if ok {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+fn bench_diff(baseline: &str, current: &str, options: &Options) -> ExitCode {
+    let (baseline, current) = match (BenchReport::load(baseline), BenchReport::load(current)) {
+        (Ok(baseline), Ok(current)) => (baseline, current),
+        (Err(e), _) | (_, Err(e)) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let regressions = current.regressions(&baseline, options.threshold);
+    for regression in &regressions {
+        println!("REGRESSED {regression}");
+    }
+
+    if regressions.is_empty() {
+        println!("no regressions above {:.0}%", options.threshold * 100.0);
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
+
+/// A detailed breakdown of a day's answer, for the days that have one.
+fn explanation(day: u8, input: &str, options: &Options) -> Result<String, String> {
+    match day {
+        1 => {
+            let lists = day1::input_gen(input).map_err(|e| e.to_string())?;
+            let [left, right, ..] = lists.as_slice() else {
+                return Ok(day1::explain_distance(&[], &[]).to_string());
+            };
+
+            Ok(day1::explain_distance(left, right).to_string())
+        }
+        2 => {
+            let reports = day2::input_gen(input).map_err(|e| e.to_string())?;
+            let mut lines = Vec::new();
+            let mut dampened = 0;
+
+            for (idx, report) in reports.iter().enumerate() {
+                let verdict = day2::report_verdict(report, &day2::SafetyRules::default());
+                if !verdict.is_safe() {
+                    dampened += verdict.is_dampened_safe() as usize;
+                    lines.push(format!(
+                        "report {:>4}: {}",
+                        idx + 1,
+                        day2::annotate(report, &verdict)
+                    ));
+                }
+            }
+
+            lines.push(format!(
+                "\n{} of {} reports unsafe, {dampened} of them fixed by the dampener",
+                lines.len(),
+                reports.len()
+            ));
+
+            Ok(lines.join("\n"))
+        }
+        3 if options.render.is_some() => {
+            day3::render(input, options.render.unwrap()).map_err(|e| e.to_string())
+        }
+        3 if options.audit => {
+            let lines = day3::Registry::standard()
+                .audit(input, "mul")
+                .iter()
+                .map(|r| {
+                    let excerpt = input[r.offset..]
+                        .char_indices()
+                        .take_while(|(idx, _)| *idx <= r.at - r.offset)
+                        .map(|(_, c)| c)
+                        .collect::<String>();
+
+                    format!("{:>8} {:<20} {excerpt:?}", r.offset, r.reason.to_string())
+                })
+                .collect::<Vec<_>>();
+
+            Ok(lines.join("\n"))
+        }
+        3 => {
+            let lines = day3::lex(input)
+                .iter()
+                .map(|t| format!("{:>8}..{:<8} {}", t.span.start, t.span.end, t.instruction))
+                .collect::<Vec<_>>();
+
+            Ok(lines.join("\n"))
+        }
+        _ => Err(format!("no explanation for day {day}")),
+    }
+}
+
+fn explain(day: u8, options: &Options) -> ExitCode {
+    let path = options
+        .input
+        .clone()
+        .unwrap_or_else(|| format!("{}/day{day}.txt", options.input_dir));
+
+    match read_input(&path).and_then(|input| explanation(day, &input, options)) {
+        Ok(explanation) => {
+            println!("{explanation}");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(e) if e.is_empty() => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {e}\n\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let solvers = match options.command {
+        Command::Run { day, part } => {
+            let solvers = solution::solvers()
+                .iter()
+                .filter(|s| s.day == day && part.is_none_or(|part| s.part == part))
+                .collect::<Vec<_>>();
+
+            if solvers.is_empty() {
+                match part {
+                    Some(part) => eprintln!("error: no solver for day {day}, part {part}"),
+                    None => eprintln!("error: no solver for day {day}"),
+                }
+                return ExitCode::FAILURE;
+            }
+
+            solvers
+        }
+        Command::All => solution::solvers().iter().collect(),
+        Command::Verify => return verify(&options),
+        Command::CrossCheck { day } => return cross_check(day, &options),
+        Command::Generate { day } => return generate(day, &options),
+        Command::Bench { day } => return bench(day, &options),
+        Command::BenchDiff {
+            ref baseline,
+            ref current,
+        } => return bench_diff(baseline, current, &options),
+        Command::Explain { day } => return explain(day, &options),
+    };
+
+    let outcomes = run(&solvers, |day| {
+        options
+            .input
+            .clone()
+            .unwrap_or_else(|| format!("{}/day{day}.txt", options.input_dir))
+    });
+
+    if options.json {
+        print_json(&outcomes);
+    } else {
+        print_text(&outcomes);
+    }
+
+    if outcomes.iter().all(|o| o.result.is_ok()) {
+        ExitCode::SUCCESS
+    } else {
+        ExitCode::FAILURE
+    }
+}
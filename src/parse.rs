@@ -0,0 +1,213 @@
+use std::{error::Error, fmt, str::FromStr};
+
+use crate::grid::GridError;
+
+/// A malformed puzzle input. Line and column numbers start at 1, columns
+/// count characters rather than bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub day: u8,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+    text: String,
+}
+
+impl ParseError {
+    /// Builds an error pointing into `input`, echoing the offending line.
+    pub fn new(
+        day: u8,
+        input: &str,
+        line: usize,
+        column: usize,
+        message: impl Into<String>,
+    ) -> Self {
+        Self {
+            day,
+            line,
+            column,
+            message: message.into(),
+            text: input
+                .lines()
+                .nth(line.saturating_sub(1))
+                .unwrap_or_default()
+                .to_string(),
+        }
+    }
+
+    /// Builds an error pointing just past the last non-blank line of `input`.
+    pub fn at_end(day: u8, input: &str, message: impl Into<String>) -> Self {
+        match lines(day, input).last() {
+            Some(line) => line.error_at_end(message),
+            None => Self::new(day, input, 1, 1, message),
+        }
+    }
+
+    pub fn from_grid(day: u8, input: &str, err: GridError) -> Self {
+        match err {
+            GridError::Empty => Self::at_end(day, input, err.to_string()),
+            GridError::Ragged {
+                line,
+                expected,
+                found,
+            } => Self::new(
+                day,
+                input,
+                line,
+                found.min(expected) + 1,
+                format!("expected {expected} cells like the first line, found {found}"),
+            ),
+            GridError::InvalidCell { line, column, cell } => Self::new(
+                day,
+                input,
+                line,
+                column,
+                format!("unexpected character {cell:?}"),
+            ),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let gutter = self.line.to_string().len();
+        // Keep tabs so the caret lines up with the echoed text.
+        let indent = self
+            .text
+            .chars()
+            .take(self.column.saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect::<String>();
+
+        writeln!(
+            f,
+            "day {}: {} at line {}, column {}",
+            self.day, self.message, self.line, self.column
+        )?;
+        writeln!(f, "{:gutter$} |", "")?;
+        writeln!(f, "{} | {}", self.line, self.text)?;
+        write!(f, "{:gutter$} | {indent}^", "")
+    }
+}
+
+impl Error for ParseError {}
+
+/// A single line of puzzle input, used to point errors at the text it holds.
+#[derive(Debug, Clone, Copy)]
+pub struct Line<'a> {
+    day: u8,
+    number: usize,
+    text: &'a str,
+}
+
+impl<'a> Line<'a> {
+    pub fn number(&self) -> usize {
+        self.number
+    }
+
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    fn column_of(&self, fragment: &str) -> usize {
+        let start = self.text.as_ptr() as usize;
+        let offset = (fragment.as_ptr() as usize)
+            .checked_sub(start)
+            .filter(|offset| *offset <= self.text.len())
+            .unwrap_or(self.text.len());
+
+        self.text[..offset].chars().count() + 1
+    }
+
+    /// Builds an error pointing at `fragment`, which must be a slice of this
+    /// line's text.
+    pub fn error_at(&self, fragment: &str, message: impl Into<String>) -> ParseError {
+        ParseError {
+            day: self.day,
+            line: self.number,
+            column: self.column_of(fragment),
+            message: message.into(),
+            text: self.text.to_string(),
+        }
+    }
+
+    pub fn error_at_end(&self, message: impl Into<String>) -> ParseError {
+        self.error_at(&self.text[self.text.len()..], message)
+    }
+
+    /// Parses `fragment`, a slice of this line's text, reporting failures at
+    /// its position.
+    pub fn parse<T>(&self, fragment: &'a str) -> Result<T, ParseError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        fragment
+            .parse()
+            .map_err(|e| self.error_at(fragment, format!("invalid number {fragment:?} ({e})")))
+    }
+
+    /// Parses every whitespace-separated field on this line.
+    pub fn parse_fields<T>(&self) -> Result<Vec<T>, ParseError>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        self.text
+            .split_whitespace()
+            .map(|field| self.parse(field))
+            .collect()
+    }
+
+    /// Splits this line once on `separator`, reporting a missing separator.
+    pub fn split_once(&self, separator: char) -> Result<(&'a str, &'a str), ParseError> {
+        self.text
+            .split_once(separator)
+            .ok_or_else(|| self.error_at_end(format!("expected {separator:?}")))
+    }
+}
+
+/// The lines of `input`, accepting both LF and CRLF endings and ignoring
+/// trailing blank lines.
+pub fn lines(day: u8, input: &str) -> impl Iterator<Item = Line<'_>> {
+    let count = input
+        .lines()
+        .enumerate()
+        .filter(|(_, l)| !l.trim().is_empty())
+        .map(|(idx, _)| idx + 1)
+        .last()
+        .unwrap_or(0);
+
+    input
+        .lines()
+        .take(count)
+        .enumerate()
+        .map(move |(idx, text)| Line {
+            day,
+            number: idx + 1,
+            text,
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn diagnostic_points_at_column() {
+        let l = lines(1, "12 ab").next().unwrap();
+        let err = l.parse::<i64>(&l.text()[3..]).unwrap_err();
+
+        assert_eq!((err.line, err.column), (1, 4));
+        assert!(err.to_string().ends_with("  |\n1 | 12 ab\n  |    ^"));
+    }
+
+    #[test]
+    fn lines_skip_trailing_blank_lines() {
+        let lines = lines(1, "a\r\n\r\nb\r\n\r\n \n")
+            .map(|l| l.text())
+            .collect::<Vec<_>>();
+
+        assert_eq!(lines, vec!["a", "", "b"]);
+    }
+}
@@ -0,0 +1,240 @@
+use std::{
+    any::Any,
+    fmt,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    day1, day10, day11, day2, day3, day4, day5, day6, day7, day8, day9, parse::ParseError,
+};
+
+/// The result of a puzzle part, regardless of the type a solver returns.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Answer {
+    Number(i128),
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(n) => write!(f, "{n}"),
+            Answer::Text(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+macro_rules! answer_from_number {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(n: $t) -> Self {
+                    Answer::Number(n as i128)
+                }
+            }
+        )*
+    };
+}
+
+answer_from_number!(i32, i64, i128, u32, u64, usize);
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+/// A part that has no answer for a well formed input, such as one too large
+/// for the solver's arithmetic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolveError {
+    pub day: u8,
+    pub message: String,
+}
+
+impl SolveError {
+    pub fn new(day: u8, message: impl Into<String>) -> Self {
+        Self {
+            day,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "day {}: {}", self.day, self.message)
+    }
+}
+
+impl std::error::Error for SolveError {}
+
+/// Why running a solver produced no answer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RunError {
+    Parse(ParseError),
+    Solve(SolveError),
+}
+
+impl fmt::Display for RunError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RunError::Parse(e) => write!(f, "{e}"),
+            RunError::Solve(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for RunError {}
+
+impl From<ParseError> for RunError {
+    fn from(e: ParseError) -> Self {
+        RunError::Parse(e)
+    }
+}
+
+impl From<SolveError> for RunError {
+    fn from(e: SolveError) -> Self {
+        RunError::Solve(e)
+    }
+}
+
+/// A day's puzzle: how to read the input and how to answer both parts.
+pub trait Solution {
+    const DAY: u8;
+
+    type Input: 'static;
+
+    fn parse(input: &str) -> Result<Self::Input, ParseError>;
+
+    fn part1(input: &Self::Input) -> Result<Answer, SolveError>;
+
+    fn part2(input: &Self::Input) -> Result<Answer, SolveError>;
+}
+
+/// A day's parsed input, produced by [`Solver::parse`].
+pub struct Parsed(Box<dyn Any>);
+
+/// An answer along with how long it took to parse the input and to solve.
+#[derive(Debug, Clone)]
+pub struct Timed {
+    pub answer: Answer,
+    pub parse: Duration,
+    pub solve: Duration,
+}
+
+/// One part of one day, callable without knowing the day's input type.
+#[derive(Clone, Copy)]
+pub struct Solver {
+    pub day: u8,
+    pub part: u8,
+    parse: fn(&str) -> Result<Parsed, ParseError>,
+    solve: fn(&Parsed) -> Result<Answer, SolveError>,
+}
+
+fn parse_boxed<S: Solution>(input: &str) -> Result<Parsed, ParseError> {
+    S::parse(input).map(|input| Parsed(Box::new(input)))
+}
+
+fn downcast<S: Solution>(input: &Parsed) -> &S::Input {
+    input
+        .0
+        .downcast_ref()
+        .unwrap_or_else(|| panic!("input was not parsed by a day {} solver", S::DAY))
+}
+
+fn solve_part1<S: Solution>(input: &Parsed) -> Result<Answer, SolveError> {
+    S::part1(downcast::<S>(input))
+}
+
+fn solve_part2<S: Solution>(input: &Parsed) -> Result<Answer, SolveError> {
+    S::part2(downcast::<S>(input))
+}
+
+impl Solver {
+    const fn part1<S: Solution>() -> Self {
+        Self {
+            day: S::DAY,
+            part: 1,
+            parse: parse_boxed::<S>,
+            solve: solve_part1::<S>,
+        }
+    }
+
+    const fn part2<S: Solution>() -> Self {
+        Self {
+            day: S::DAY,
+            part: 2,
+            parse: parse_boxed::<S>,
+            solve: solve_part2::<S>,
+        }
+    }
+
+    pub fn parse(&self, input: &str) -> Result<Parsed, ParseError> {
+        (self.parse)(input)
+    }
+
+    /// Solves already parsed input. Panics if `input` was parsed by a solver
+    /// for a different day.
+    pub fn solve(&self, input: &Parsed) -> Result<Answer, SolveError> {
+        (self.solve)(input)
+    }
+
+    pub fn run(&self, input: &str) -> Result<Answer, RunError> {
+        Ok(self.solve(&self.parse(input)?)?)
+    }
+
+    pub fn run_timed(&self, input: &str) -> Result<Timed, RunError> {
+        let start = Instant::now();
+        let parsed = self.parse(input)?;
+        let parse = start.elapsed();
+
+        let start = Instant::now();
+        let answer = self.solve(&parsed)?;
+        let solve = start.elapsed();
+
+        Ok(Timed {
+            answer,
+            parse,
+            solve,
+        })
+    }
+}
+
+impl fmt::Debug for Solver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Solver")
+            .field("day", &self.day)
+            .field("part", &self.part)
+            .finish()
+    }
+}
+
+macro_rules! solvers {
+    ($($day:ty),* $(,)?) => {
+        [$(Solver::part1::<$day>(), Solver::part2::<$day>()),*]
+    };
+}
+
+static SOLVERS: [Solver; 22] = solvers![
+    day1::Day1,
+    day2::Day2,
+    day3::Day3,
+    day4::Day4,
+    day5::Day5,
+    day6::Day6,
+    day7::Day7,
+    day8::Day8,
+    day9::Day9,
+    day10::Day10,
+    day11::Day11,
+];
+
+/// Every registered solver, ordered by day then part.
+pub fn solvers() -> &'static [Solver] {
+    &SOLVERS
+}
+
+pub fn solver(day: u8, part: u8) -> Option<&'static Solver> {
+    SOLVERS.iter().find(|s| s.day == day && s.part == part)
+}
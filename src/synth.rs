@@ -0,0 +1,405 @@
+//! Seeded synthetic puzzle inputs, in the exact format each day's
+//! `input_gen` accepts.
+
+use std::fmt::Write;
+
+use crate::{
+    geometry::{BoundingBox, Direction8, Position},
+    rng::Rng,
+};
+
+/// How big and how busy a generated input is. What `size` and `density`
+/// mean depends on the day, see the individual generators.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Params {
+    pub size: usize,
+    pub density: f64,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Self {
+            size: 100,
+            density: 0.5,
+        }
+    }
+}
+
+/// Generates an input for `day`, or `None` if there is no generator for it.
+pub fn generate(day: u8, rng: &mut Rng, params: &Params) -> Option<String> {
+    let generate = match day {
+        1 => day1,
+        2 => day2,
+        3 => day3,
+        4 => day4,
+        5 => day5,
+        6 => day6,
+        7 => day7,
+        8 => day8,
+        9 => day9,
+        10 => day10,
+        11 => day11,
+        _ => return None,
+    };
+
+    Some(generate(rng, params))
+}
+
+fn grid(size: usize, mut cell: impl FnMut(usize, usize) -> char) -> String {
+    let mut out = String::with_capacity(size * (size + 1));
+
+    for y in 0..size {
+        out.extend((0..size).map(|x| cell(x, y)));
+        out.push('\n');
+    }
+
+    out
+}
+
+/// `size` pairs of five digit location IDs. `density` is the chance a right
+/// hand ID is copied from the left list, which drives the similarity score.
+pub fn day1(rng: &mut Rng, params: &Params) -> String {
+    let left = (0..params.size)
+        .map(|_| rng.range(10_000..=99_999))
+        .collect::<Vec<_>>();
+
+    let mut out = String::new();
+    for a in &left {
+        let b = if rng.chance(params.density) {
+            *rng.choose(&left)
+        } else {
+            rng.range(10_000..=99_999)
+        };
+
+        writeln!(out, "{a}   {b}").unwrap();
+    }
+
+    out
+}
+
+/// `size` reports of 5 to 8 levels. `density` is the chance each step
+/// follows the safety rules, so higher densities give more safe reports.
+pub fn day2(rng: &mut Rng, params: &Params) -> String {
+    let mut out = String::new();
+
+    for _ in 0..params.size {
+        let increasing = rng.chance(0.5);
+        let mut level = rng.range(10..=90);
+        let mut report = vec![level];
+
+        for _ in 1..rng.range(5..=8) {
+            let step = if rng.chance(params.density) {
+                rng.range(1..=3)
+            } else {
+                rng.range(-4..=5)
+            };
+
+            level = (if increasing {
+                level + step
+            } else {
+                level - step
+            })
+            .max(1);
+            report.push(level);
+        }
+
+        let report = report.iter().map(i64::to_string).collect::<Vec<_>>();
+        writeln!(out, "{}", report.join(" ")).unwrap();
+    }
+
+    out
+}
+
+/// About `size` bytes of corrupted memory. `density` is the share of
+/// fragments that are well formed `mul`, `do()` or `don't()` instructions.
+pub fn day3(rng: &mut Rng, params: &Params) -> String {
+    const JUNK: &[&str] = &[
+        "mul(", "mul[", "mul ( ", ",", ")", "do(", "don't", "what()", "select()", "#", "%", "<",
+        ">", "{", "'", "?", "^", "+", "!", "@", "from()", "mul(4*", "-", " ", "who()",
+    ];
+
+    let mut out = String::new();
+
+    while out.len() < params.size {
+        if rng.chance(params.density) {
+            match rng.below(6) {
+                0 => out.push_str("do()"),
+                1 => out.push_str("don't()"),
+                _ => write!(out, "mul({},{})", rng.range(0..=999), rng.range(0..=999)).unwrap(),
+            }
+        } else {
+            out.push_str(rng.choose::<&str>(JUNK));
+        }
+    }
+
+    out.push('\n');
+    out
+}
+
+/// A `size` by `size` grid of `XMAS` letters. `density` is the chance a
+/// cell starts a planted `XMAS` in a random direction.
+pub fn day4(rng: &mut Rng, params: &Params) -> String {
+    const LETTERS: [char; 4] = ['X', 'M', 'A', 'S'];
+
+    let size = params.size.max(1);
+    let mut cells = (0..size * size)
+        .map(|_| *rng.choose(&LETTERS))
+        .collect::<Vec<_>>();
+
+    for idx in 0..cells.len() {
+        if !rng.chance(params.density) {
+            continue;
+        }
+
+        let step = rng.choose(&Direction8::ALL).offset();
+        let start = Position((idx % size) as i64, (idx / size) as i64);
+        let bounds = BoundingBox::new(Position::ORIGIN, Position(size as i64 - 1, size as i64 - 1));
+
+        if bounds.contains(start + step * 3) {
+            for (i, c) in LETTERS.iter().enumerate() {
+                let p = start + step * i as i64;
+                cells[p.1 as usize * size + p.0 as usize] = *c;
+            }
+        }
+    }
+
+    grid(size, |x, y| cells[y * size + x])
+}
+
+/// Ordering rules between up to `size` two digit pages (at most 90), then
+/// `size` updates of an odd number of pages. The rules are a subset of one
+/// random total order, `density` being the share of pairs that get a rule.
+/// Updates are shuffled half of the time.
+pub fn day5(rng: &mut Rng, params: &Params) -> String {
+    let mut pages = (10..=99).collect::<Vec<i64>>();
+    rng.shuffle(&mut pages);
+    pages.truncate(params.size.clamp(1, pages.len()));
+
+    let mut out = String::new();
+    for (i, a) in pages.iter().enumerate() {
+        for b in &pages[i + 1..] {
+            if rng.chance(params.density) {
+                writeln!(out, "{a}|{b}").unwrap();
+            }
+        }
+    }
+    out.push('\n');
+
+    for _ in 0..params.size.max(1) {
+        let longest = pages.len() - 1 + pages.len() % 2;
+        let len = (rng.below(12) as usize * 2 + 1).min(longest);
+
+        let mut picked = (0..pages.len()).collect::<Vec<_>>();
+        rng.shuffle(&mut picked);
+        picked.truncate(len);
+        if rng.chance(0.5) {
+            picked.sort();
+        }
+
+        let update = picked
+            .iter()
+            .map(|idx| pages[*idx].to_string())
+            .collect::<Vec<_>>();
+        writeln!(out, "{}", update.join(",")).unwrap();
+    }
+
+    out
+}
+
+/// A `size` by `size` lab with a guard facing up. `density` is the chance
+/// a cell holds an obstruction.
+pub fn day6(rng: &mut Rng, params: &Params) -> String {
+    let size = params.size.max(1);
+    let guard = rng.below((size * size) as u64) as usize;
+
+    grid(size, |x, y| {
+        if y * size + x == guard {
+            '^'
+        } else if rng.chance(params.density) {
+            '#'
+        } else {
+            '.'
+        }
+    })
+}
+
+/// `size` equations of 2 to 8 numbers below 100. `density` is the chance
+/// the test value is reachable with `+`, `*` and `||`.
+pub fn day7(rng: &mut Rng, params: &Params) -> String {
+    let mut out = String::new();
+
+    for _ in 0..params.size {
+        let nums = (0..rng.range(2..=8))
+            .map(|_| rng.range(1..=99))
+            .collect::<Vec<_>>();
+
+        let value = if rng.chance(params.density) {
+            nums[1..].iter().fold(nums[0], |acc, n| match rng.below(3) {
+                0 => acc + n,
+                1 => acc * n,
+                _ => acc * 10_i64.pow(n.ilog10() + 1) + n,
+            })
+        } else {
+            rng.range(1..=nums.iter().product::<i64>())
+        };
+
+        let nums = nums.iter().map(i64::to_string).collect::<Vec<_>>();
+        writeln!(out, "{value}: {}", nums.join(" ")).unwrap();
+    }
+
+    out
+}
+
+/// A `size` by `size` map of antennae. `density` is the chance a cell holds
+/// an antenna, each tuned to one of eight frequencies.
+pub fn day8(rng: &mut Rng, params: &Params) -> String {
+    const FREQUENCIES: [char; 8] = ['0', '7', 'a', 'k', 'z', 'A', 'Q', 'Z'];
+
+    grid(params.size.max(1), |_, _| {
+        if rng.chance(params.density) {
+            *rng.choose(&FREQUENCIES)
+        } else {
+            '.'
+        }
+    })
+}
+
+/// A disk map of `size` digits. Files are 0 to 9 blocks long, `density` is
+/// the chance a free span is not empty.
+pub fn day9(rng: &mut Rng, params: &Params) -> String {
+    let mut out = (0..params.size.max(1))
+        .map(|idx| {
+            let len = if idx % 2 == 0 {
+                rng.range(0..=9)
+            } else if rng.chance(params.density) {
+                rng.range(1..=9)
+            } else {
+                0
+            };
+
+            char::from_digit(len as u32, 10).unwrap()
+        })
+        .collect::<String>();
+
+    out.push('\n');
+    out
+}
+
+/// A `size` by `size` topographic map sloping down from peaks of height 9.
+/// `density` is the chance of a peak per ten cells.
+pub fn day10(rng: &mut Rng, params: &Params) -> String {
+    let size = params.size.max(1);
+    let mut peaks = (0..size * size)
+        .filter(|_| rng.chance(params.density / 10.0))
+        .map(|idx| (idx % size, idx / size))
+        .collect::<Vec<_>>();
+
+    if peaks.is_empty() {
+        peaks.push((
+            rng.below(size as u64) as usize,
+            rng.below(size as u64) as usize,
+        ));
+    }
+
+    grid(size, |x, y| {
+        let distance = peaks
+            .iter()
+            .map(|(px, py)| px.abs_diff(x) + py.abs_diff(y))
+            .min()
+            .unwrap();
+
+        char::from_digit(9 - distance.min(9) as u32, 10).unwrap()
+    })
+}
+
+/// `size` stones on one line. `density` is the share of single digit
+/// stones, the rest are below a million.
+pub fn day11(rng: &mut Rng, params: &Params) -> String {
+    let stones = (0..params.size.max(1))
+        .map(|_| {
+            if rng.chance(params.density) {
+                rng.range(0..=9)
+            } else {
+                rng.range(10..=999_999)
+            }
+            .to_string()
+        })
+        .collect::<Vec<_>>();
+
+    stones.join(" ") + "\n"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solution;
+
+    #[test]
+    fn generated_inputs_parse() {
+        for day in 1..=11 {
+            for seed in 0..5 {
+                for density in [0.0, 0.3, 1.0] {
+                    let params = Params {
+                        size: 1 + seed as usize * 7,
+                        density,
+                    };
+                    let input = generate(day, &mut Rng::new(seed), &params).unwrap();
+
+                    assert!(
+                        solution::solver(day, 1).unwrap().parse(&input).is_ok(),
+                        "day {day}, seed {seed}, density {density}:\n{input}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn generated_inputs_solve() {
+        let params = Params {
+            size: 12,
+            ..Params::default()
+        };
+
+        for solver in solution::solvers() {
+            let input = generate(solver.day, &mut Rng::new(0), &params).unwrap();
+
+            assert!(
+                solver.run(&input).is_ok(),
+                "day {}, part {}",
+                solver.day,
+                solver.part
+            );
+        }
+
+        // Guards boxed in by obstructions used to spin forever.
+        let params = Params {
+            size: 30,
+            ..Params::default()
+        };
+        for seed in 0..20 {
+            let input = generate(6, &mut Rng::new(seed), &params).unwrap();
+
+            for part in [1, 2] {
+                assert!(solution::solver(6, part).unwrap().run(&input).is_ok());
+            }
+        }
+    }
+
+    #[test]
+    fn same_seed_same_input() {
+        let params = Params::default();
+
+        for day in 1..=11 {
+            assert_eq!(
+                generate(day, &mut Rng::new(42), &params),
+                generate(day, &mut Rng::new(42), &params)
+            );
+        }
+    }
+
+    #[test]
+    fn unknown_day() {
+        assert_eq!(generate(25, &mut Rng::new(0), &Params::default()), None);
+    }
+}
@@ -0,0 +1,317 @@
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+
+use crate::solution::{self, Answer, Timed};
+
+/// Expected answers for a set of inputs, read from a TOML file of the form:
+///
+/// ```toml
+/// [[case]]
+/// day = 1
+/// part = 1
+/// input = "input/examples/day1.txt"
+/// answer = 11
+/// ```
+///
+/// Input paths are relative to the manifest's directory.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Manifest {
+    #[serde(rename = "case", default)]
+    pub cases: Vec<Case>,
+    #[serde(skip)]
+    base_dir: PathBuf,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Case {
+    pub day: u8,
+    pub part: u8,
+    pub input: PathBuf,
+    pub answer: Expected,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum Expected {
+    Number(i64),
+    Text(String),
+}
+
+impl Expected {
+    pub fn matches(&self, answer: &Answer) -> bool {
+        match (self, answer) {
+            (Expected::Number(expected), Answer::Number(n)) => *expected as i128 == *n,
+            _ => self.to_string() == answer.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expected::Number(n) => write!(f, "{n}"),
+            Expected::Text(s) => write!(f, "{s}"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ManifestError {
+    Io(PathBuf, io::Error),
+    Toml(PathBuf, toml::de::Error),
+}
+
+impl fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ManifestError::Io(path, e) => write!(f, "cannot read {}: {e}", path.display()),
+            ManifestError::Toml(path, e) => write!(f, "invalid manifest {}: {e}", path.display()),
+        }
+    }
+}
+
+impl std::error::Error for ManifestError {}
+
+#[derive(Debug, Clone)]
+pub enum Status {
+    Pass,
+    Mismatch { actual: Answer },
+    Fail(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Report<'a> {
+    pub case: &'a Case,
+    pub status: Status,
+    pub timing: Option<Timed>,
+}
+
+impl Manifest {
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ManifestError> {
+        let path = path.as_ref();
+        let text =
+            fs::read_to_string(path).map_err(|e| ManifestError::Io(path.to_path_buf(), e))?;
+        let mut manifest: Manifest =
+            toml::from_str(&text).map_err(|e| ManifestError::Toml(path.to_path_buf(), e))?;
+
+        manifest.base_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+
+        Ok(manifest)
+    }
+
+    pub fn input_path(&self, case: &Case) -> PathBuf {
+        self.base_dir.join(&case.input)
+    }
+
+    /// Runs every case in order.
+    pub fn verify(&self) -> Vec<Report<'_>> {
+        self.cases
+            .iter()
+            .map(|case| self.verify_case(case))
+            .collect()
+    }
+
+    fn verify_case<'a>(&self, case: &'a Case) -> Report<'a> {
+        let fail = |reason: String| Report {
+            case,
+            status: Status::Fail(reason),
+            timing: None,
+        };
+
+        let Some(solver) = solution::solver(case.day, case.part) else {
+            return fail(format!(
+                "no solver for day {}, part {}",
+                case.day, case.part
+            ));
+        };
+
+        let path = self.input_path(case);
+        let input = match fs::read_to_string(&path) {
+            Ok(input) => input,
+            Err(e) => return fail(format!("cannot read {}: {e}", path.display())),
+        };
+
+        match solver.run_timed(&input) {
+            Ok(timed) => Report {
+                case,
+                status: if case.answer.matches(&timed.answer) {
+                    Status::Pass
+                } else {
+                    Status::Mismatch {
+                        actual: timed.answer.clone(),
+                    }
+                },
+                timing: Some(timed),
+            },
+            Err(e) => fail(e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo_dir() -> &'static Path {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+    }
+
+    /// A manifest of `toml` cases with inputs relative to the repository.
+    fn manifest(toml: &str) -> Manifest {
+        Manifest {
+            base_dir: repo_dir().to_path_buf(),
+            ..toml::from_str(toml).unwrap()
+        }
+    }
+
+    #[test]
+    fn parse_cases() {
+        let manifest: Manifest = toml::from_str(
+            r#"
+            [[case]]
+            day = 1
+            part = 1
+            input = "input/examples/day1.txt"
+            answer = 11
+
+            [[case]]
+            day = 11
+            part = 2
+            input = "../big.txt"
+            answer = "170141183460469231731687303715884105727"
+            "#,
+        )
+        .unwrap();
+
+        let cases = manifest
+            .cases
+            .iter()
+            .map(|c| (c.day, c.part, c.input.to_str().unwrap(), c.answer.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            cases,
+            [
+                (1, 1, "input/examples/day1.txt", Expected::Number(11)),
+                (
+                    11,
+                    2,
+                    "../big.txt",
+                    Expected::Text("170141183460469231731687303715884105727".into())
+                ),
+            ]
+        );
+
+        let empty: Manifest = toml::from_str("").unwrap();
+        assert!(empty.cases.is_empty());
+    }
+
+    #[test]
+    fn load_relative_to_manifest() {
+        let manifest = Manifest::load(repo_dir().join("answers.toml")).unwrap();
+        let case = &manifest.cases[0];
+
+        assert_eq!(case.input, Path::new("input/examples/day1.txt"));
+        assert_eq!(
+            manifest.input_path(case),
+            repo_dir().join("input/examples/day1.txt")
+        );
+
+        let nested = Manifest {
+            base_dir: PathBuf::from("answers"),
+            ..manifest.clone()
+        };
+        assert_eq!(
+            nested.input_path(case),
+            Path::new("answers/input/examples/day1.txt")
+        );
+    }
+
+    #[test]
+    fn load_errors() {
+        assert!(matches!(
+            Manifest::load(repo_dir().join("missing.toml")),
+            Err(ManifestError::Io(..))
+        ));
+        assert!(matches!(
+            Manifest::load(repo_dir().join("input/examples/day3.txt")),
+            Err(ManifestError::Toml(..))
+        ));
+    }
+
+    #[test]
+    fn expected_matches() {
+        assert!(Expected::Number(11).matches(&Answer::Number(11)));
+        assert!(!Expected::Number(11).matches(&Answer::Number(12)));
+        assert!(Expected::Number(-3).matches(&Answer::Text("-3".into())));
+
+        let big = Expected::Text(i128::MAX.to_string());
+        assert!(big.matches(&Answer::Number(i128::MAX)));
+        assert!(!big.matches(&Answer::Number(i128::MAX - 1)));
+
+        assert!(Expected::Text("6,0,7".into()).matches(&Answer::Text("6,0,7".into())));
+        assert!(!Expected::Text("011".into()).matches(&Answer::Number(11)));
+    }
+
+    #[test]
+    fn statuses() {
+        let manifest = manifest(
+            r#"
+            [[case]]
+            day = 1
+            part = 1
+            input = "input/examples/day1.txt"
+            answer = 11
+
+            [[case]]
+            day = 1
+            part = 2
+            input = "input/examples/day1.txt"
+            answer = 30
+
+            [[case]]
+            day = 25
+            part = 1
+            input = "input/examples/day1.txt"
+            answer = 0
+
+            [[case]]
+            day = 1
+            part = 1
+            input = "input/examples/missing.txt"
+            answer = 11
+
+            [[case]]
+            day = 1
+            part = 1
+            input = "input/examples/day3.txt"
+            answer = 11
+            "#,
+        );
+        let reports = manifest.verify();
+
+        assert!(matches!(reports[0].status, Status::Pass));
+        assert!(reports[0].timing.is_some());
+
+        assert!(matches!(
+            &reports[1].status,
+            Status::Mismatch { actual } if *actual == Answer::Number(31)
+        ));
+        assert!(reports[1].timing.is_some());
+
+        let reasons = reports[2..]
+            .iter()
+            .map(|report| match &report.status {
+                Status::Fail(reason) if report.timing.is_none() => reason.as_str(),
+                status => panic!("expected a failure, got {status:?}"),
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(reasons[0], "no solver for day 25, part 1");
+        assert!(reasons[1].starts_with("cannot read "));
+        assert!(reasons[1].contains("missing.txt"));
+        assert!(reasons[2].contains("expected at least two location IDs"));
+    }
+}